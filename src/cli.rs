@@ -0,0 +1,763 @@
+use crate::display::display_board;
+use crate::game::{can_tutor, game_loop, graph_game_loop, TUTOR_RULES};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use terminal_lights_out::file::{
+    board_rows, light_character, load_puzzle, parse_light, parse_puzzle_with_states, parse_size,
+    parse_target, save_puzzle, target_rows, write_puzzle,
+};
+use terminal_lights_out::graph::load_graph;
+use terminal_lights_out::variant::parse_states;
+use terminal_lights_out::{
+    random_seed, solve_by_chasing, solve_by_search, solve_graph, solve_lights_out, solve_lit_only,
+    Difficulty, Game, Generation, GraphGame, NumberBoard, Point, Restriction, Shape, SolveError,
+    Variant,
+};
+
+pub const USAGE: &str = "Usage: terminal-lights-out [COMMAND] [OPTIONS]
+
+Commands:
+  play        Start a game straight away
+  solve       Print the minimum-press solution for a board
+  generate    Print a random solvable board as a puzzle file
+  verify      Check that a board is solvable, or that presses solve it
+  crosscheck  Compare the solver with a brute-force search on small boards, reporting any difference
+  help        Show this message
+
+Running without a command opens the interactive menu.
+
+Options:
+  -s, --size <SIZE>       Board size, e.g. 5 or 7x5 (default 5x5)
+      --shape <SHAPE>     Board shape: rectangle, cross, diamond or ring (default rectangle)
+  -v, --variant <RULES>   Rules to play by: classic, hex or torus, plus a press pattern: x, square,
+                          knight, big-plus or stencil=ROWS, sigma to leave the pressed light
+                          alone and lit-only to allow pressing only lit lights, e.g. 'torus knight'
+                          (default classic)
+  -k, --states <K>        Number of states each light cycles through, 2 to 36 (default 2, on/off)
+      --seed <SEED>       Seed for reproducible boards (play, generate, crosscheck)
+  -g, --generator <GEN>   How boards are generated: uniform (any solvable board, equally likely)
+                          or presses (10 to 30 random presses) (default uniform)
+  -d, --difficulty <LVL>  Generate a board whose optimal solution suits the level:
+                          easy, medium, hard or expert
+  -b, --board <ROWS>      Board rows separated by '/', using '.' or '0' for off, '#' or '1' for on
+                          and '-' for a hole
+  -i, --input <PATH>      Load the board from a puzzle file (play, solve, verify)
+      --graph <PATH>      Play on a graph loaded from an edge-list file instead of a grid (play, solve, verify);
+                          presses are then a single row with one entry per node
+  -o, --output <PATH>     Save the generated board to a puzzle file (generate)
+  -p, --presses <ROWS>    Presses to apply when verifying, in the same form as --board,
+                          with a digit for the number of times to press each button
+  -t, --target <TARGET>   Picture to solve towards instead of all off: off, on, checkerboard, heart
+                          or rows in the same form as --board
+  -n, --count <N>         Boards to compare (crosscheck): a number of random boards, or all to compare
+                          every board of the size (default 1000)
+  -f, --format <FORMAT>   Output format: text or json (default text)
+      --tutor             Walk through solving the board by light chasing, marking each press (play)
+      --solver <SOLVER>   How to solve classic boards (solve): elimination, or chasing for very large
+                          boards without holes (default elimination)
+      --cache-dir <DIR>   Keep the solver's tables for classic boards in DIR, so later runs on boards
+                          of the same size, shape and rules skip working them out";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Subcommand {
+    Play,
+    Solve,
+    Generate,
+    Verify,
+    Crosscheck,
+    Help,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Text,
+    Json,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Solver {
+    Elimination,
+    Chasing,
+}
+
+// How many boards `crosscheck` compares the solvers on.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Count {
+    All,
+    Random(u64),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Arguments {
+    pub subcommand: Option<Subcommand>,
+    pub size: Option<(i32, i32)>,
+    pub shape: Option<Shape>,
+    pub variant: Option<Variant>,
+    pub states: Option<u8>,
+    pub seed: Option<u64>,
+    pub generation: Option<Generation>,
+    pub board: Option<String>,
+    pub input: Option<String>,
+    pub graph: Option<String>,
+    pub output: Option<String>,
+    pub presses: Option<String>,
+    pub target: Option<String>,
+    pub cache_dir: Option<String>,
+    pub format: Format,
+    pub solver: Solver,
+    pub tutor: bool,
+    pub count: Count,
+}
+
+pub fn parse_arguments(args: impl IntoIterator<Item = String>) -> Result<Arguments, String> {
+    let mut arguments = Arguments {
+        subcommand: None,
+        size: None,
+        shape: None,
+        variant: None,
+        states: None,
+        seed: None,
+        generation: None,
+        board: None,
+        input: None,
+        graph: None,
+        output: None,
+        presses: None,
+        target: None,
+        cache_dir: None,
+        format: Format::Text,
+        solver: Solver::Elimination,
+        tutor: false,
+        count: Count::Random(1000),
+    };
+    let mut args = args.into_iter();
+
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| {
+            args.next()
+                .ok_or_else(|| format!("Missing value for {}", name))
+        };
+
+        match arg.as_str() {
+            "-s" | "--size" => {
+                let size = value(&arg)?;
+                arguments.size = Some(parse_size(&size).ok_or_else(|| {
+                    format!("Invalid board size: '{}', expected e.g. 5 or 7x5", size)
+                })?);
+            }
+            "--shape" => {
+                let shape = value(&arg)?;
+                arguments.shape = Some(
+                    Shape::from_name(&shape)
+                        .ok_or_else(|| format!("Unknown shape: '{}'", shape))?,
+                );
+            }
+            "-v" | "--variant" => {
+                let variant = value(&arg)?;
+                arguments.variant = Some(Variant::from_name(&variant)?);
+            }
+            "-k" | "--states" => {
+                arguments.states = Some(parse_states(&value(&arg)?)?);
+            }
+            "--seed" => {
+                let seed = value(&arg)?;
+                arguments.seed = Some(
+                    seed.parse()
+                        .map_err(|_| format!("Invalid seed: '{}', expected a number", seed))?,
+                );
+            }
+            "-g" | "--generator" => {
+                arguments.generation = Some(match value(&arg)?.as_str() {
+                    "uniform" => Generation::UniformSolvable,
+                    "presses" => Generation::RandomPresses,
+                    generator => return Err(format!("Unknown generator: '{}'", generator)),
+                })
+            }
+            "-d" | "--difficulty" => {
+                let difficulty = value(&arg)?;
+                arguments.generation = Some(Generation::Difficulty(
+                    Difficulty::from_name(&difficulty)
+                        .ok_or_else(|| format!("Unknown difficulty: '{}'", difficulty))?,
+                ));
+            }
+            "-b" | "--board" => arguments.board = Some(value(&arg)?),
+            "-i" | "--input" => arguments.input = Some(value(&arg)?),
+            "--graph" => arguments.graph = Some(value(&arg)?),
+            "-o" | "--output" => arguments.output = Some(value(&arg)?),
+            "-p" | "--presses" => arguments.presses = Some(value(&arg)?),
+            "-t" | "--target" => arguments.target = Some(value(&arg)?),
+            "--cache-dir" => arguments.cache_dir = Some(value(&arg)?),
+            "-f" | "--format" => {
+                arguments.format = match value(&arg)?.as_str() {
+                    "text" => Format::Text,
+                    "json" => Format::Json,
+                    format => return Err(format!("Unknown format: '{}'", format)),
+                }
+            }
+            "--solver" => {
+                arguments.solver = match value(&arg)?.as_str() {
+                    "elimination" => Solver::Elimination,
+                    "chasing" => Solver::Chasing,
+                    solver => return Err(format!("Unknown solver: '{}'", solver)),
+                }
+            }
+            "--tutor" => arguments.tutor = true,
+            "-n" | "--count" => {
+                let count = value(&arg)?;
+                arguments.count = match count.as_str() {
+                    "all" => Count::All,
+                    _ => Count::Random(count.parse().map_err(|_| {
+                        format!("Invalid count: '{}', expected a number or all", count)
+                    })?),
+                }
+            }
+            "-h" | "--help" => arguments.subcommand = Some(Subcommand::Help),
+            _ if arguments.subcommand.is_none() && !arg.starts_with('-') => {
+                arguments.subcommand = Some(match arg.as_str() {
+                    "play" => Subcommand::Play,
+                    "solve" => Subcommand::Solve,
+                    "generate" => Subcommand::Generate,
+                    "verify" => Subcommand::Verify,
+                    "crosscheck" => Subcommand::Crosscheck,
+                    "help" => Subcommand::Help,
+                    _ => return Err(format!("Unknown command: '{}'", arg)),
+                });
+            }
+            _ => return Err(format!("Unexpected argument: '{}'", arg)),
+        }
+    }
+
+    Ok(arguments)
+}
+
+impl Arguments {
+    // The rules for a new board: --variant, with the number of states from --states.
+    pub fn variant(&self) -> Option<Variant> {
+        match (&self.variant, self.states) {
+            (None, None) => None,
+            (variant, states) => {
+                let mut variant = variant.clone().unwrap_or_default();
+                variant.states = states.unwrap_or(variant.states);
+                Some(variant)
+            }
+        }
+    }
+
+    // Applies --variant and --states on top of the rules a loaded board came with.
+    pub fn override_variant(&self, game: &mut Game) -> Result<(), String> {
+        let mut variant = game.variant().clone();
+        if let Some(rules) = &self.variant {
+            variant = Variant {
+                states: variant.states,
+                ..rules.clone()
+            };
+        }
+        variant.states = self.states.unwrap_or(variant.states);
+        variant.check_height(game.height())?;
+        if game
+            .board()
+            .iter()
+            .chain(game.target())
+            .flatten()
+            .any(|square| square.state() >= variant.states)
+        {
+            return Err(format!(
+                "The board or target has lights in states past the {} states given",
+                variant.states
+            ));
+        }
+        game.set_variant(variant);
+
+        Ok(())
+    }
+
+    // Applies --target, which replaces the target a loaded board came with.
+    pub fn apply_target(&self, game: &mut Game) -> Result<(), String> {
+        if let Some(target) = &self.target {
+            let target = parse_target(target, game.width(), game.height(), game.variant().states)
+                .map_err(|message| format!("Invalid target: {}", message))?;
+            game.set_target(target);
+        }
+
+        Ok(())
+    }
+}
+
+// Parses board rows separated by '/', e.g. "#.#/.../#.#", using the puzzle file format for each row.
+fn parse_board(input: &str, states: Option<u8>) -> Result<Game, String> {
+    // Blank lines are skipped in puzzle files, but an empty row here is a mistake, like '##//##'
+    if let Some(row) = input.split('/').position(|row| row.trim().is_empty()) {
+        return Err(format!("Invalid board: row {} is empty", row + 1));
+    }
+    parse_puzzle_with_states(&input.replace('/', "\n"), states)
+        .map_err(|error| format!("Invalid board: {}", error))
+}
+
+// Parses presses in the same form as --board, where a digit presses a button that many times.
+fn parse_presses(input: &str) -> Result<NumberBoard, String> {
+    let presses: NumberBoard = input
+        .split('/')
+        .map(|row| {
+            row.chars()
+                .filter(|character| !character.is_whitespace())
+                .map(|character| parse_light(character).map(|count| count as i32))
+                .collect::<Option<Vec<i32>>>()
+        })
+        .collect::<Option<NumberBoard>>()
+        .ok_or_else(|| format!("Invalid presses: '{}'", input))?;
+
+    Ok(presses)
+}
+
+fn json_rows(rows: &[String]) -> String {
+    let rows: Vec<String> = rows.iter().map(|row| format!("\"{}\"", row)).collect();
+    format!("[{}]", rows.join(","))
+}
+
+fn json_number_board(board: &NumberBoard) -> String {
+    let rows: Vec<String> = board
+        .iter()
+        .map(|row| {
+            let row: Vec<String> = row.iter().map(|value| value.to_string()).collect();
+            format!("[{}]", row.join(","))
+        })
+        .collect();
+    format!("[{}]", rows.join(","))
+}
+
+fn display_number_board(board: &NumberBoard) {
+    for row in board {
+        let row: Vec<String> = row.iter().map(|value| value.to_string()).collect();
+        println!("{}", row.join(" "));
+    }
+}
+
+// An unsolvable board as JSON: the checks it fails, or for boards that were searched rather than solved, a fixed
+// code for why there is no solution, since the text of the other errors spans several lines.
+fn json_error(error: &SolveError) -> String {
+    match error {
+        SolveError::Unsolvable {
+            violated_checks, ..
+        } => {
+            let checks: Vec<String> = violated_checks.iter().map(json_number_board).collect();
+            format!(
+                "{{\"solvable\":false,\"violated_checks\":[{}]}}",
+                checks.join(",")
+            )
+        }
+        SolveError::LitOnlyUnsolvable => {
+            "{\"solvable\":false,\"error\":\"lit-only-unsolvable\"}".to_string()
+        }
+        SolveError::Unreachable => "{\"solvable\":false,\"error\":\"unreachable\"}".to_string(),
+        SolveError::SearchTooLarge { lights } => format!(
+            "{{\"solvable\":false,\"error\":\"search-too-large\",\"lights\":{}}}",
+            lights
+        ),
+    }
+}
+
+// Prints the presses of a lit-only solution in the order they must be made, as 1-based column and row,
+// returning the exit code.
+fn print_lit_only_solution(game: &Game, format: Format) -> i32 {
+    match (solve_lit_only(game), format) {
+        (Ok(presses), Format::Text) => {
+            for point in &presses {
+                println!("{} {}", point.x + 1, point.y + 1);
+            }
+            0
+        }
+        (Ok(presses), Format::Json) => {
+            let order: Vec<String> = presses
+                .iter()
+                .map(|point| format!("[{},{}]", point.x + 1, point.y + 1))
+                .collect();
+            println!(
+                "{{\"solvable\":true,\"order\":[{}],\"total_presses\":{}}}",
+                order.join(","),
+                presses.len()
+            );
+            0
+        }
+        (Err(error), Format::Text) => {
+            print!("{}", error);
+            1
+        }
+        (Err(error), Format::Json) => {
+            println!("{}", json_error(&error));
+            1
+        }
+    }
+}
+
+fn new_game(arguments: &Arguments) -> Result<Game, String> {
+    let (width, height) = arguments.size.unwrap_or((5, 5));
+    let variant = arguments.variant().unwrap_or_default();
+    variant.check_height(height)?;
+    let mut game =
+        Game::with_variant(width, height, variant).expect("the size and rules were checked");
+    arguments.apply_target(&mut game)?;
+    game.set_mask(arguments.shape.unwrap_or_default().mask(width, height));
+    game.generate_board(
+        arguments.seed.unwrap_or_else(random_seed),
+        arguments.generation.unwrap_or_default(),
+    )
+    .map_err(|error| error.to_string())?;
+
+    Ok(game)
+}
+
+// Loads the board from --board or --input. A --variant or --states given on the command line overrides the one in the file.
+fn loaded_board(arguments: &Arguments) -> Result<Option<Game>, String> {
+    let mut game = match (&arguments.board, &arguments.input) {
+        (Some(_), Some(_)) => return Err("Use either --board or --input, not both".to_string()),
+        (Some(board), None) => parse_board(board, arguments.states)?,
+        (None, Some(path)) => {
+            load_puzzle(path).map_err(|error| format!("Failed to load '{}': {}", path, error))?
+        }
+        (None, None) => return Ok(None),
+    };
+    arguments.override_variant(&mut game)?;
+    arguments.apply_target(&mut game)?;
+
+    Ok(Some(game))
+}
+
+fn loaded_graph(arguments: &Arguments) -> Result<Option<GraphGame>, String> {
+    let Some(path) = &arguments.graph else {
+        return Ok(None);
+    };
+    if arguments.board.is_some() || arguments.input.is_some() {
+        return Err("Use either --graph or a grid board, not both".to_string());
+    }
+
+    load_graph(path)
+        .map(Some)
+        .map_err(|error| format!("Failed to load '{}': {}", path, error))
+}
+
+// Applies the presses, one count per node, and reports whether they turn every light off.
+fn verify_graph(mut game: GraphGame, presses: &str, format: Format) -> Result<i32, String> {
+    let presses = parse_presses(presses)?;
+    let [presses] = &presses[..] else {
+        return Err("Presses for a graph must be a single row".to_string());
+    };
+    if presses.len() != game.graph().node_count() {
+        return Err("Presses must have one entry per node".to_string());
+    }
+    for (node, &count) in presses.iter().enumerate() {
+        for _ in 0..count {
+            game.press(node);
+        }
+    }
+
+    let solved = game.solved();
+    let lights: String = game
+        .lights()
+        .iter()
+        .map(|light| light_character(light, game.states()))
+        .collect();
+    match format {
+        Format::Text if solved => println!("Solved"),
+        Format::Text => println!("Not solved, lights left: {}", lights),
+        Format::Json => println!("{{\"solved\":{},\"lights\":\"{}\"}}", solved, lights),
+    }
+    Ok(if solved { 0 } else { 1 })
+}
+
+// Presses each button the number of times given for it.
+fn apply_presses(game: &mut Game, presses: &NumberBoard) {
+    for (y, row) in presses.iter().enumerate() {
+        for (x, &count) in row.iter().enumerate() {
+            for _ in 0..count {
+                game.toggle_light(&Point::new(x as i32, y as i32));
+            }
+        }
+    }
+}
+
+// A board where the solver and the brute-force search disagree. Press counts are None when a solver found
+// the board unsolvable.
+struct Discrepancy {
+    board: Vec<String>,
+    elimination: Option<i32>,
+    search: Option<i32>,
+    elimination_solves: bool,
+}
+
+// Compares `solve_lights_out` with `solve_by_search` on the board: both must agree on whether it is solvable and
+// on the fewest presses, and the solver's presses must solve it.
+fn compare_solvers(game: &Game) -> Result<Option<Discrepancy>, String> {
+    let elimination = match solve_lights_out(game) {
+        Ok(solution) => Some(solution),
+        Err(SolveError::Unsolvable { .. }) => None,
+        Err(error) => return Err(error.to_string().trim().to_string()),
+    };
+    let search = match solve_by_search(game) {
+        Ok(solution) => Some(solution),
+        Err(SolveError::Unreachable) => None,
+        Err(error) => return Err(error.to_string().trim().to_string()),
+    };
+
+    let total = |solution: &NumberBoard| solution.iter().flatten().sum::<i32>();
+    let elimination_solves = elimination.as_ref().is_none_or(|solution| {
+        let mut game = game.clone();
+        apply_presses(&mut game, solution);
+        game.solved()
+    });
+    let elimination = elimination.as_ref().map(total);
+    let search = search.as_ref().map(total);
+    if elimination == search && elimination_solves {
+        return Ok(None);
+    }
+
+    Ok(Some(Discrepancy {
+        board: board_rows(game),
+        elimination,
+        search,
+        elimination_solves,
+    }))
+}
+
+// Compares the solvers on every board of the size, shape, rules and target given, or on random boards.
+fn crosscheck(arguments: &Arguments) -> Result<i32, String> {
+    let (width, height) = arguments.size.unwrap_or((5, 5));
+    let variant = arguments.variant().unwrap_or_default();
+    variant.check_height(height)?;
+    let mut template =
+        Game::with_variant(width, height, variant).expect("the size and rules were checked");
+    arguments.apply_target(&mut template)?;
+    template.set_mask(arguments.shape.unwrap_or_default().mask(width, height));
+    let cells = template.cells();
+    let states = template.variant().states;
+    let boards = (states as u64).saturating_pow(cells.len() as u32);
+
+    // Each board is numbered by its lights as base-k digits, first cell lowest
+    let board = |mut number: u64| {
+        let mut game = template.clone();
+        for point in &cells {
+            for _ in 0..number % states as u64 {
+                game.toggle_single_light(point);
+            }
+            number /= states as u64;
+        }
+        game
+    };
+    let numbers: Box<dyn Iterator<Item = u64>> = match arguments.count {
+        Count::All => Box::new(0..boards),
+        Count::Random(count) => {
+            let mut rand = ChaCha8Rng::seed_from_u64(arguments.seed.unwrap_or_else(random_seed));
+            Box::new((0..count).map(move |_| rand.gen_range(0..boards)))
+        }
+    };
+
+    let mut checked = 0;
+    let mut discrepancies = Vec::new();
+    for number in numbers {
+        checked += 1;
+        if let Some(discrepancy) = compare_solvers(&board(number))? {
+            if arguments.format == Format::Text {
+                print_discrepancy(&discrepancy);
+            }
+            discrepancies.push(discrepancy);
+        }
+    }
+
+    match arguments.format {
+        Format::Text => println!(
+            "Checked {} boards, found {} discrepancies",
+            checked,
+            discrepancies.len()
+        ),
+        Format::Json => {
+            let discrepancies: Vec<String> = discrepancies
+                .iter()
+                .map(|discrepancy| {
+                    let presses = |total: Option<i32>| total.map_or("null".to_string(), |total| total.to_string());
+                    format!(
+                        "{{\"board\":{},\"elimination\":{},\"search\":{},\"elimination_solves\":{}}}",
+                        json_rows(&discrepancy.board),
+                        presses(discrepancy.elimination),
+                        presses(discrepancy.search),
+                        discrepancy.elimination_solves
+                    )
+                })
+                .collect();
+            println!(
+                "{{\"checked\":{},\"discrepancies\":[{}]}}",
+                checked,
+                discrepancies.join(",")
+            );
+        }
+    }
+    Ok(if discrepancies.is_empty() { 0 } else { 1 })
+}
+
+fn print_discrepancy(discrepancy: &Discrepancy) {
+    let presses = |total: Option<i32>| {
+        total.map_or("unsolvable".to_string(), |total| {
+            format!("{} presses", total)
+        })
+    };
+    println!("Discrepancy on:");
+    for row in &discrepancy.board {
+        println!("{}", row);
+    }
+    println!(
+        "Elimination: {}, search: {}",
+        presses(discrepancy.elimination),
+        presses(discrepancy.search)
+    );
+    if !discrepancy.elimination_solves {
+        println!("The elimination solution does not solve the board");
+    }
+    println!();
+}
+
+fn required_board(arguments: &Arguments) -> Result<Game, String> {
+    loaded_board(arguments)?.ok_or_else(|| "Missing --board or --input".to_string())
+}
+
+// Runs a subcommand, returning the process exit code.
+pub fn run(subcommand: Subcommand, arguments: &Arguments) -> Result<i32, String> {
+    match subcommand {
+        Subcommand::Help => {
+            println!("{}", USAGE);
+            Ok(0)
+        }
+        Subcommand::Play => {
+            if let Some(mut game) = loaded_graph(arguments)? {
+                game.calculate_shortest_solution()
+                    .map_err(|error| error.to_string())?;
+                graph_game_loop(game);
+                return Ok(0);
+            }
+            let mut game = match loaded_board(arguments)? {
+                Some(game) => game,
+                None => new_game(arguments)?,
+            };
+            if arguments.tutor && !can_tutor(&game) {
+                return Err(TUTOR_RULES.to_string());
+            }
+            // Boards too large for a lit-only search can still be played, without a best solve to beat
+            match game.calculate_shortest_solution() {
+                Ok(_) | Err(SolveError::SearchTooLarge { .. }) => {}
+                Err(error) => return Err(error.to_string()),
+            }
+            game_loop(game, arguments.tutor);
+            Ok(0)
+        }
+        Subcommand::Generate => {
+            let mut game = new_game(arguments)?;
+            // Generated boards are always solvable, but lit-only boards may be too large to search
+            let shortest_solution = game.calculate_shortest_solution().ok();
+            if let Some(path) = &arguments.output {
+                save_puzzle(&game, path)
+                    .map_err(|error| format!("Failed to save '{}': {}", path, error))?;
+            }
+            match arguments.format {
+                Format::Text => print!("{}", write_puzzle(&game)),
+                Format::Json => println!(
+                    "{{\"width\":{},\"height\":{},\"seed\":{},\"board\":{},\"target\":{},\"shortest_solution\":{}}}",
+                    game.width(),
+                    game.height(),
+                    game.seed().unwrap_or_default(),
+                    json_rows(&board_rows(&game)),
+                    json_rows(&target_rows(&game)),
+                    shortest_solution.map_or("null".to_string(), |presses| presses.to_string())
+                ),
+            }
+            Ok(0)
+        }
+        Subcommand::Solve => {
+            // A graph's solution is a single row with one entry per node
+            let solution = match loaded_graph(arguments)? {
+                Some(game) => solve_graph(&game).map(|solution| vec![solution]),
+                None => {
+                    let game = required_board(arguments)?;
+                    if game.variant().restriction == Restriction::LitOnly {
+                        return Ok(print_lit_only_solution(&game, arguments.format));
+                    }
+                    match arguments.solver {
+                        Solver::Elimination => solve_lights_out(&game),
+                        Solver::Chasing => solve_by_chasing(&game),
+                    }
+                }
+            };
+            match (solution, arguments.format) {
+                (Ok(solution), Format::Text) => {
+                    display_number_board(&solution);
+                    Ok(0)
+                }
+                (Ok(solution), Format::Json) => {
+                    println!(
+                        "{{\"solvable\":true,\"presses\":{},\"total_presses\":{}}}",
+                        json_number_board(&solution),
+                        solution.iter().flatten().sum::<i32>()
+                    );
+                    Ok(0)
+                }
+                (Err(error), Format::Text) => {
+                    print!("{}", error);
+                    Ok(1)
+                }
+                (Err(error), Format::Json) => {
+                    println!("{}", json_error(&error));
+                    Ok(1)
+                }
+            }
+        }
+        Subcommand::Crosscheck => crosscheck(arguments),
+        Subcommand::Verify => {
+            let graph = loaded_graph(arguments)?;
+            let Some(presses) = &arguments.presses else {
+                let solvable = match &graph {
+                    Some(game) => solve_graph(game).is_ok(),
+                    None => {
+                        let game = required_board(arguments)?;
+                        match game.variant().restriction {
+                            Restriction::LitOnly => solve_lit_only(&game).is_ok(),
+                            Restriction::None => solve_lights_out(&game).is_ok(),
+                        }
+                    }
+                };
+                match arguments.format {
+                    Format::Text if solvable => println!("Solvable"),
+                    Format::Text => println!("Not solvable"),
+                    Format::Json => println!("{{\"solvable\":{}}}", solvable),
+                }
+                return Ok(if solvable { 0 } else { 1 });
+            };
+            if let Some(game) = graph {
+                return verify_graph(game, presses, arguments.format);
+            }
+
+            let mut game = required_board(arguments)?;
+            let presses = parse_presses(presses)?;
+            if presses.len() != game.height() as usize
+                || presses.iter().any(|row| row.len() != game.width() as usize)
+            {
+                return Err("Presses must be the same size as the board".to_string());
+            }
+            apply_presses(&mut game, &presses);
+
+            let solved = game.solved();
+            match arguments.format {
+                Format::Text if solved => println!("Solved"),
+                Format::Text if game.has_target() => {
+                    println!("Not solved, the board does not match the target:");
+                    display_board(&game);
+                }
+                Format::Text => {
+                    println!("Not solved, lights left on:");
+                    display_board(&game);
+                }
+                Format::Json => println!(
+                    "{{\"solved\":{},\"board\":{}}}",
+                    solved,
+                    json_rows(&board_rows(&game))
+                ),
+            }
+            Ok(if solved { 0 } else { 1 })
+        }
+    }
+}
@@ -0,0 +1,52 @@
+use std::ops::RangeInclusive;
+
+/// Difficulty presets, defined by how many presses the optimal solution takes
+/// as a share of the longest optimal solution the board can have.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+    Expert,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 4] = [
+        Difficulty::Easy,
+        Difficulty::Medium,
+        Difficulty::Hard,
+        Difficulty::Expert,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "Easy",
+            Difficulty::Medium => "Medium",
+            Difficulty::Hard => "Hard",
+            Difficulty::Expert => "Expert",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Difficulty> {
+        Difficulty::ALL
+            .into_iter()
+            .find(|difficulty| difficulty.name().eq_ignore_ascii_case(name.trim()))
+    }
+
+    /// The accepted optimal solution lengths on a board whose longest optimal solution, as given by
+    /// `Game::longest_solution`, is `longest` presses: up to 20% of it for Easy, 35% for Medium, 50% for Hard
+    /// and anything above for Expert. On a 5x5 board, where it is 14, that is 1-3, 4-5, 6-7 and 8 or more presses.
+    pub fn press_range(&self, longest: i32) -> RangeInclusive<i32> {
+        let share = |fraction: f64| (longest as f64 * fraction).ceil() as i32;
+        let (start, end) = match self {
+            Difficulty::Easy => (1, share(0.2)),
+            Difficulty::Medium => (share(0.2) + 1, share(0.35)),
+            Difficulty::Hard => (share(0.35) + 1, share(0.5)),
+            Difficulty::Expert => (share(0.5) + 1, i32::MAX),
+        };
+
+        // Very small boards can leave a preset with no lengths of its own
+        let start = start.min(longest).max(1);
+        start..=end.max(start)
+    }
+}
@@ -1,5 +1,10 @@
-use crossterm::{cursor, terminal, ExecutableCommand};
-use std::io;
+use crossterm::{
+    cursor,
+    style::{Color, Stylize},
+    terminal, ExecutableCommand,
+};
+use std::io::{self, Write};
+use terminal_lights_out::{Game, GraphGame, Grid, NumberBoard, Point, Square};
 
 pub fn refresh_display(lines: i32) {
     for _ in 0..lines {
@@ -10,28 +15,193 @@ pub fn refresh_display(lines: i32) {
     }
 }
 
+// Prints the message and reads a single line of input from the user.
+pub fn prompt(message: &str) -> String {
+    print!("{}", message);
+    io::stdout().flush().unwrap();
+    let mut input = String::new();
+    io::stdin()
+        .read_line(&mut input)
+        .expect("Failed to read input");
+    refresh_display(1);
+
+    input
+}
+
+// Classic lights are drawn plain; lights with more states also get a colour per state.
+fn visual(square: &Square, states: u8) -> String {
+    const COLORS: [Color; 6] = [
+        Color::Red,
+        Color::Green,
+        Color::Blue,
+        Color::Yellow,
+        Color::Magenta,
+        Color::Cyan,
+    ];
+    let glyph = square.to_visual(states);
+    if states == 2 || square.state() == 0 {
+        return glyph;
+    }
+
+    let color = COLORS[(square.state() as usize - 1) % COLORS.len()];
+    glyph.with(color).to_string()
+}
+
+// Marks the selected light with |x| and the hinted light, if any, with <x> ([x] when they overlap).
+pub fn display_board_with_selector(game: &Game, point: &Point, hint: Option<&Point>) {
+    draw_board(game, Some(point), hint);
+}
+
+pub fn display_board(game: &Game) {
+    draw_board(game, None, None);
+}
+
+// Renders each cell through `cell` into a line per row, leaving holes blank. Hex boards get a gap after
+// every cell and shift odd rows by half a cell, so each cell sits between the two cells above it.
+fn cell_rows(game: &Game, mut cell: impl FnMut(&Point) -> String) -> Vec<String> {
+    let hex = game.variant().grid == Grid::Hex;
+    (0..game.height())
+        .map(|y| {
+            let mut row = String::new();
+            if hex && y % 2 == 1 {
+                row.push_str("  ");
+            }
+            for x in 0..game.width() {
+                let current = Point::new(x, y);
+                if game.is_present(&current) {
+                    row.push_str(&cell(&current));
+                } else {
+                    row.push_str("   ");
+                }
+                if hex {
+                    row.push(' ');
+                }
+            }
+            // Odd rows of a hex board start shifted, so pad even rows to line up anything drawn after them
+            if hex && y % 2 == 0 {
+                row.push_str("  ");
+            }
+            row
+        })
+        .collect()
+}
+
+fn draw_cells(game: &Game, cell: impl FnMut(&Point) -> String) {
+    for row in cell_rows(game, cell) {
+        println!("{}", row);
+    }
+}
+
+// Draws the board, followed on each row by the target when the goal is a picture, with an arrow
+// between them on the middle row.
+fn draw_board(game: &Game, point: Option<&Point>, hint: Option<&Point>) {
+    let states = game.variant().states;
+    let board = cell_rows(game, |current| {
+        let light = visual(&game.square(current), states);
+        let selected = point == Some(current);
+        let hinted = hint == Some(current);
+        match (selected, hinted) {
+            (true, true) => format!("[{}]", light),
+            (true, false) => format!("|{}|", light),
+            (false, true) => format!("<{}>", light),
+            (false, false) => format!(" {} ", light),
+        }
+    });
+    if !game.has_target() {
+        for row in board {
+            println!("{}", row);
+        }
+        return;
+    }
+
+    let target = cell_rows(game, |current| {
+        format!(" {} ", visual(&game.target_square(current), states))
+    });
+    let middle = board.len() / 2;
+    for (y, (row, target)) in board.iter().zip(&target).enumerate() {
+        let separator = if y == middle { "  →  " } else { "     " };
+        println!("{}{}{}", row, separator, target);
+    }
+}
+
+pub fn display_solution(game: &Game, solution: &NumberBoard) {
+    println!("Toggle the lights with numbers in any order");
+    draw_cells(game, |point| {
+        format!(" {} ", solution[point.y as usize][point.x as usize])
+    });
+}
+
+// Lists the presses of a lit-only solution as 1-based column and row, since a light may be pressed more than once.
+pub fn display_press_order(order: &[Point]) {
+    let presses: Vec<String> = order
+        .iter()
+        .map(|point| format!("({}, {})", point.x + 1, point.y + 1))
+        .collect();
+    println!(
+        "Press these lights in order, each while it is lit: {}",
+        presses.join(", ")
+    );
+}
+
+// Lists each node with its light and the nodes it is joined to, returning the number of lines printed.
+pub fn display_graph(game: &GraphGame) -> i32 {
+    let graph = game.graph();
+    for node in 0..graph.node_count() {
+        let neighbours: Vec<String> = graph
+            .neighbours(node)
+            .iter()
+            .map(|neighbour| neighbour.to_string())
+            .collect();
+        println!(
+            "{:>3} {}  joined to {}",
+            node,
+            visual(&game.square(node), game.states()),
+            neighbours.join(", ")
+        );
+    }
+
+    graph.node_count() as i32
+}
+
+pub fn display_graph_solution(solution: &[i32]) {
+    let presses: Vec<String> = solution
+        .iter()
+        .enumerate()
+        .filter(|(_, &count)| count > 0)
+        .map(|(node, &count)| match count {
+            1 => node.to_string(),
+            count => format!("{} (x{})", node, count),
+        })
+        .collect();
+    println!("Press these nodes in any order: {}", presses.join(", "));
+}
+
 pub fn welcome() {
     println!("==============================");
     println!("      Welcome to Lights Out   ");
     println!("      Created by cqb13        ");
     println!("      GitHub: github.com/cqb13");
     println!("==============================");
-    print!("\n");
+    println!();
 
     println!("Controls:");
     println!("  Move with arrows (←↑↓→)");
+    println!("  On hex boards ↑↓ move up-left and down-left, Shift+↑↓ up-right and down-right");
     println!("  Enter to select");
+    println!("  'u' to undo, Ctrl-r to redo");
+    println!("  'h' for a hint");
+    println!("  'w' to save the board to a file");
     println!("  'q' to quit");
-    print!("\n");
+    println!();
 
     println!("Instructions:");
-    println!("  Turn off all the lights.");
+    println!("  Turn off all the lights, or match the target shown beside the board.");
     println!("  Pressing a light will toggle adjacent blocks.");
-    print!("\n");
+    println!();
 
     println!("Enjoy the game!");
     println!("==============================");
-    print!("\n");
+    println!();
 }
 
 use crossterm::event::{read, Event, KeyCode, KeyEvent, KeyEventKind};
@@ -72,6 +242,14 @@ impl OptionSelect {
         self
     }
 
+    pub fn add_option_if(self, condition: bool, option: &str) -> Self {
+        if condition {
+            self.add_option(option)
+        } else {
+            self
+        }
+    }
+
     pub fn ask(&self) -> String {
         let mut current_option = 0;
         if self.options.is_empty() {
@@ -90,12 +268,13 @@ impl OptionSelect {
             terminal::enable_raw_mode().expect("Failed to enable raw mode");
 
             let event = read().unwrap();
-            match event {
-                Event::Key(KeyEvent {
-                    code,
-                    kind: KeyEventKind::Press,
-                    ..
-                }) => match code {
+            if let Event::Key(KeyEvent {
+                code,
+                kind: KeyEventKind::Press,
+                ..
+            }) = event
+            {
+                match code {
                     KeyCode::Char('q') => {
                         terminal::disable_raw_mode().unwrap();
                         std::process::exit(0);
@@ -122,8 +301,7 @@ impl OptionSelect {
                         return self.options[current_option].to_string();
                     }
                     _ => {}
-                },
-                _ => {}
+                }
             }
             terminal::disable_raw_mode().expect("Failed to disable raw mode");
             refresh_display(self.reset_size);
@@ -0,0 +1,397 @@
+//! Plain-text puzzle files.
+//!
+//! A puzzle file is an optional header followed by one line per board row:
+//!
+//! ```text
+//! ; comments start with a semicolon
+//! size: 5x5
+//! variant: classic
+//! .#...
+//! ###..
+//! .#...
+//! .....
+//! .....
+//! ```
+//!
+//! Lights are written as `.` or `0` when off and `#` or `1` when on. Games with more than two states
+//! write each light's state as a digit, continuing with letters (`a` = 10) past 9. A `-` marks a hole, a cell
+//! that is not part of the board, for puzzles that are not rectangular. Spaces between lights are ignored.
+//! Header lines are `key: value` pairs and must come before the board:
+//!
+//! - `size`: the board size as `WIDTHxHEIGHT` (or a single number for square boards). If given, the board must match it.
+//! - `variant`: the rules, as keywords separated by spaces (e.g. `torus knight`). The board is `classic` (the default),
+//!   `torus` for a board whose edges wrap around, or `hex` for a board of hexagons. `sigma` leaves the pressed light
+//!   alone, `sigma-plus` toggles it as usual, and `lit-only` allows pressing only lit lights. On square grids a press
+//!   pattern can be added: `plus` (the default), `x`, `square`, `knight`, `big-plus` or `stencil=ROWS`
+//!   (e.g. `stencil=#.#/.#./#.#`).
+//! - `states`: how many states each light cycles through, 2 (the default) for classic on/off lights.
+//! - `seed`: the seed the board was generated from, recorded so it can be reproduced.
+//! - `target`: the picture the board must match to be solved, instead of every light off. Either a built-in
+//!   picture, `off`, `on`, `checkerboard` or `heart`, or rows separated by `/` written like the board, e.g. `#.#/.#./#.#`.
+//!
+//! Blank lines and comments may appear anywhere.
+
+use crate::variant::{parse_states, MAX_STATES};
+use crate::{Board, Game, Point, Square, Target, Variant};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    /// The 1-based line the error was found on.
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[derive(Debug)]
+pub enum LoadError {
+    Io(io::Error),
+    Parse(ParseError),
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadError::Io(error) => write!(f, "{}", error),
+            LoadError::Parse(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for LoadError {}
+
+impl From<io::Error> for LoadError {
+    fn from(error: io::Error) -> Self {
+        LoadError::Io(error)
+    }
+}
+
+impl From<ParseError> for LoadError {
+    fn from(error: ParseError) -> Self {
+        LoadError::Parse(error)
+    }
+}
+
+pub(crate) fn error(line: usize, message: String) -> ParseError {
+    ParseError { line, message }
+}
+
+/// Parses a board size written as "7" (square) or "7x5" (width x height).
+pub fn parse_size(input: &str) -> Option<(i32, i32)> {
+    let input = input.trim().to_lowercase();
+    let (width, height) = match input.split_once('x') {
+        Some((width, height)) => (width.trim().parse().ok()?, height.trim().parse().ok()?),
+        None => {
+            let size = input.parse().ok()?;
+            (size, size)
+        }
+    };
+
+    if width < 1 || height < 1 {
+        return None;
+    }
+
+    Some((width, height))
+}
+
+/// The character for a light: `.` and `#` for classic lights, otherwise its state as a digit or letter.
+pub fn light_character(square: &Square, states: u8) -> char {
+    match square.state() {
+        0 => '.',
+        1 if states == 2 => '#',
+        state => char::from_digit(state as u32, MAX_STATES as u32).unwrap_or('?'),
+    }
+}
+
+/// The character for a cell of the game: `-` for a hole, otherwise its `light_character`.
+pub fn cell_character(game: &Game, point: &Point) -> char {
+    if game.is_present(point) {
+        light_character(&game.square(point), game.variant().states)
+    } else {
+        '-'
+    }
+}
+
+/// The state of the light written as `character`: `.` or `#` for off and on, otherwise a digit or letter
+/// (`a` = 10), or `None` for any other character.
+pub fn parse_light(character: char) -> Option<u8> {
+    match character {
+        '.' => Some(0),
+        '#' => Some(1),
+        _ => character
+            .to_digit(MAX_STATES as u32)
+            .map(|state| state as u8),
+    }
+}
+
+/// The board's rows written as in a puzzle file, one `cell_character` per cell.
+pub fn board_rows(game: &Game) -> Vec<String> {
+    (0..game.height())
+        .map(|y| {
+            (0..game.width())
+                .map(|x| cell_character(game, &Point::new(x, y)))
+                .collect()
+        })
+        .collect()
+}
+
+/// The target's rows written as in a puzzle file, one `light_character` per cell.
+pub fn target_rows(game: &Game) -> Vec<String> {
+    game.target()
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|square| light_character(square, game.variant().states))
+                .collect()
+        })
+        .collect()
+}
+
+/// Parses a target for a `width` x `height` board with `states` states: the name of a built-in `Target`,
+/// or rows separated by `/` using the same characters as a puzzle file, where `-` is treated as off.
+pub fn parse_target(input: &str, width: i32, height: i32, states: u8) -> Result<Board, String> {
+    if let Some(target) = Target::from_name(input) {
+        return Ok(target.board(width, height));
+    }
+
+    let target = input
+        .trim()
+        .split('/')
+        .map(|row| {
+            row.chars()
+                .filter(|character| !character.is_whitespace())
+                .map(|character| match character {
+                    '-' => Some(Square::OFF),
+                    _ => parse_light(character)
+                        .filter(|&state| state < states)
+                        .map(Square::new),
+                })
+                .collect::<Option<Vec<Square>>>()
+        })
+        .collect::<Option<Board>>()
+        .ok_or_else(|| {
+            format!(
+                "expected off, on, checkerboard, heart or rows of lights separated by '/', found '{}'",
+                input.trim()
+            )
+        })?;
+
+    if target.len() != height as usize || target.iter().any(|row| row.len() != width as usize) {
+        return Err(format!(
+            "the target must be {}x{}, the same size as the board",
+            width, height
+        ));
+    }
+
+    Ok(target)
+}
+
+/// Parses a puzzle in the plain-text format described in the module documentation.
+pub fn parse_puzzle(input: &str) -> Result<Game, ParseError> {
+    parse_puzzle_with_states(input, None)
+}
+
+/// Parses a puzzle like `parse_puzzle`, with lights of `states` states unless a `states` header says otherwise.
+pub fn parse_puzzle_with_states(input: &str, states: Option<u8>) -> Result<Game, ParseError> {
+    let mut size = None;
+    let mut seed = None;
+    let mut variant = Variant::default();
+    let mut variant_line = 0;
+    let mut states = states;
+    let mut target = None;
+    let mut board = Vec::new();
+    let mut mask = Vec::new();
+    let mut last_line = 0;
+
+    for (index, line) in input.lines().enumerate() {
+        let number = index + 1;
+        let line = line.trim();
+        last_line = number;
+
+        if line.is_empty() || line.starts_with(';') {
+            continue;
+        }
+
+        if let Some((key, value)) = line.split_once(':') {
+            if !board.is_empty() {
+                return Err(error(
+                    number,
+                    "header lines must come before the board".to_string(),
+                ));
+            }
+
+            let value = value.trim();
+            match key.trim().to_lowercase().as_str() {
+                "size" => {
+                    size = Some(parse_size(value).ok_or_else(|| {
+                        error(
+                            number,
+                            format!("invalid size '{}', expected e.g. 5 or 7x5", value),
+                        )
+                    })?);
+                }
+                "variant" => {
+                    variant =
+                        Variant::from_name(value).map_err(|message| error(number, message))?;
+                    variant_line = number;
+                }
+                "states" => {
+                    states = Some(parse_states(value).map_err(|message| error(number, message))?);
+                }
+                "seed" => {
+                    seed = Some(value.parse().map_err(|_| {
+                        error(
+                            number,
+                            format!("invalid seed '{}', expected a number", value),
+                        )
+                    })?);
+                }
+                "target" => target = Some((number, value.to_string())),
+                key => return Err(error(number, format!("unknown header '{}'", key))),
+            }
+            continue;
+        }
+
+        let states = *states.get_or_insert(variant.states);
+        let mut row = Vec::new();
+        let mut row_mask = Vec::new();
+        for (column, character) in line.chars().enumerate() {
+            if character.is_whitespace() {
+                continue;
+            }
+            row_mask.push(character != '-');
+            if character == '-' {
+                row.push(Square::OFF);
+                continue;
+            }
+
+            match parse_light(character) {
+                Some(state) if state < states => row.push(Square::new(state)),
+                Some(state) => {
+                    return Err(error(
+                        number,
+                        format!(
+                            "light '{}' in column {} is state {}, but lights only have {} states",
+                            character,
+                            column + 1,
+                            state,
+                            states
+                        ),
+                    ))
+                }
+                None => {
+                    return Err(error(
+                        number,
+                        format!(
+                            "invalid light '{}' in column {}, expected '.', '#', '-' or a digit",
+                            character,
+                            column + 1
+                        ),
+                    ))
+                }
+            }
+        }
+
+        if let Some(width) = board.first().map(Vec::len) {
+            if row.len() != width {
+                return Err(error(
+                    number,
+                    format!(
+                        "row has {} lights, but the first row has {}",
+                        row.len(),
+                        width
+                    ),
+                ));
+            }
+        }
+        board.push(row);
+        mask.push(row_mask);
+    }
+
+    if board.is_empty() {
+        return Err(error(
+            last_line.max(1),
+            "the puzzle has no board rows".to_string(),
+        ));
+    }
+
+    if let Some((width, height)) = size {
+        if board[0].len() != width as usize || board.len() != height as usize {
+            return Err(error(
+                last_line,
+                format!(
+                    "the header size is {}x{}, but the board is {}x{}",
+                    width,
+                    height,
+                    board[0].len(),
+                    board.len()
+                ),
+            ));
+        }
+    }
+
+    variant
+        .check_height(board.len() as i32)
+        .map_err(|message| error(variant_line, message))?;
+    let mut game = Game::from_board(board)
+        .expect("rows were checked to be the same length, with lights in a state");
+    game.set_mask(mask);
+    variant.states = states.unwrap_or(variant.states);
+    game.set_variant(variant);
+    if let Some((line, value)) = target {
+        game.set_target(
+            parse_target(&value, game.width(), game.height(), game.variant().states)
+                .map_err(|message| error(line, message))?,
+        );
+    }
+    game.set_seed(seed);
+
+    Ok(game)
+}
+
+/// Writes a game in the plain-text puzzle format, including the size, variant, states, seed and target header.
+pub fn write_puzzle(game: &Game) -> String {
+    let states = game.variant().states;
+    let mut output = format!(
+        "size: {}x{}\nvariant: {}\n",
+        game.width(),
+        game.height(),
+        game.variant().name()
+    );
+    if states != 2 {
+        output.push_str(&format!("states: {}\n", states));
+    }
+    if let Some(seed) = game.seed() {
+        output.push_str(&format!("seed: {}\n", seed));
+    }
+    if game.has_target() {
+        output.push_str(&format!("target: {}\n", target_rows(game).join("/")));
+    }
+
+    for row in board_rows(game) {
+        output.push_str(&row);
+        output.push('\n');
+    }
+
+    output
+}
+
+pub fn load_puzzle(path: impl AsRef<Path>) -> Result<Game, LoadError> {
+    let input = fs::read_to_string(path)?;
+
+    Ok(parse_puzzle(&input)?)
+}
+
+pub fn save_puzzle(game: &Game, path: impl AsRef<Path>) -> io::Result<()> {
+    fs::write(path, write_puzzle(game))
+}
@@ -1,76 +1,448 @@
-use super::{Game, Point, GRID_SIZE};
-use crate::display::refresh_display;
+use crate::display::{
+    display_board, display_board_with_selector, display_graph, prompt, refresh_display,
+};
 use crossterm::{
-    event::{read, Event, KeyCode, KeyEvent, KeyEventKind},
+    event::{read, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
     terminal,
 };
+use terminal_lights_out::file::save_puzzle;
+use terminal_lights_out::{
+    can_chase, solve_by_chasing, solve_lit_only, Game, GraphGame, Grid, Point, Restriction,
+};
 
-pub fn game_loop(mut game: Game) {
-    let mut current_point = Point::new(2, 2);
-    let mut moves = 0;
-    game.display_with_selector(&current_point);
+pub const TUTOR_RULES: &str =
+    "The tutor teaches light chasing, which works on classic and sigma boards with \
+on/off lights, no holes and no wrapping edges, where any light can be pressed";
+
+// Draws the board with the cursor, hint and an optional status line, returning the number of lines printed.
+fn draw(game: &Game, point: &Point, hint: Option<&Point>, status: Option<&str>) -> i32 {
+    display_board_with_selector(game, point, hint);
+    match status {
+        Some(status) => {
+            println!("{}", status);
+            game.height() + 1
+        }
+        None => game.height(),
+    }
+}
+
+// The step an arrow key moves the cursor by. On a hex grid up and down go to the left of the two cells
+// above or below, or the right one with Shift held; these are offsets from an unshifted row.
+fn direction(game: &Game, code: KeyCode, modifiers: KeyModifiers) -> (i32, i32) {
+    let hex = game.variant().grid == Grid::Hex;
+    let dx = if hex && !modifiers.contains(KeyModifiers::SHIFT) {
+        -1
+    } else {
+        0
+    };
+    match code {
+        KeyCode::Up if hex => (dx, -1),
+        KeyCode::Down if hex => (dx, 1),
+        KeyCode::Up => (0, -1),
+        KeyCode::Down => (0, 1),
+        KeyCode::Left => (-1, 0),
+        _ => (1, 0),
+    }
+}
+
+// Moves the cursor one cell in the direction (dx, dy), wrapping around at the edges and skipping over holes.
+// Stays put when every other cell in that line is a hole.
+fn move_cursor(game: &Game, point: Point, dx: i32, dy: i32) -> Point {
+    let mut next = point;
+    for _ in 0..game.width().max(game.height()) {
+        let dx = game.variant().grid.column_offset(next.y, dx, dy);
+        next = Point::new(
+            (next.x + dx).rem_euclid(game.width()),
+            (next.y + dy).rem_euclid(game.height()),
+        );
+        if game.is_present(&next) {
+            return next;
+        }
+    }
+
+    point
+}
+
+// Re-solves the current board, by light chasing where it works so large boards stay quick, and picks the press from the optimal solution closest to the cursor.
+// On lit-only boards the order matters, so the hint is the first press of the shortest lit-only solution.
+fn find_hint(game: &Game, cursor: &Point) -> Option<Point> {
+    if game.variant().restriction == Restriction::LitOnly {
+        return solve_lit_only(game).ok()?.first().copied();
+    }
+
+    let solution = solve_by_chasing(game).ok()?;
+    let mut presses = Vec::new();
+    for (y, row) in solution.iter().enumerate() {
+        for (x, &count) in row.iter().enumerate() {
+            if count > 0 {
+                presses.push(Point::new(x as i32, y as i32));
+            }
+        }
+    }
+
+    presses
+        .into_iter()
+        .min_by_key(|point| (point.x - cursor.x).abs() + (point.y - cursor.y).abs())
+}
+
+// Asks for a path and saves the board there, returning a status message.
+fn save_to_file(game: &Game) -> String {
+    let path = prompt("Save puzzle to: ");
+    let path = path.trim();
+    match save_puzzle(game, path) {
+        Ok(()) => format!("Saved to '{}'", path),
+        Err(error) => format!("Failed to save '{}': {}", path, error),
+    }
+}
+
+// The presses made so far, and the presses that were undone and can be redone.
+// Pressing a light once per state cancels out, so undoing a move is pressing it the remaining times.
+struct History {
+    moves: Vec<Point>,
+    undone: Vec<Point>,
+}
+
+impl History {
+    fn new() -> Self {
+        History {
+            moves: Vec::new(),
+            undone: Vec::new(),
+        }
+    }
+
+    fn press(&mut self, game: &mut Game, point: Point) {
+        game.toggle_light(&point);
+        self.moves.push(point);
+        self.undone.clear();
+    }
+
+    fn undo(&mut self, game: &mut Game) -> Option<Point> {
+        let point = self.moves.pop()?;
+        for _ in 1..game.variant().states {
+            game.toggle_light(&point);
+        }
+        self.undone.push(point);
+
+        Some(point)
+    }
+
+    fn redo(&mut self, game: &mut Game) -> Option<Point> {
+        let point = self.undone.pop()?;
+        game.toggle_light(&point);
+        self.moves.push(point);
+
+        Some(point)
+    }
+
+    fn move_count(&self) -> usize {
+        self.moves.len()
+    }
+}
+
+// Whether the tutor can walk the player through solving the board by light chasing.
+pub fn can_tutor(game: &Game) -> bool {
+    can_chase(game) && game.variant().restriction == Restriction::None
+}
+
+// Teaches light chasing: press under every light that is on, row by row, so the lights are chased down to the
+// bottom row; then press the top-row buttons that clear the pattern left there and chase down again. Lights
+// count as on when they differ from the target.
+struct Tutor {
+    // The top-row presses still to make for the bottom-row pattern last found, and that pattern
+    top_row: Vec<Point>,
+    pattern: String,
+}
+
+impl Tutor {
+    fn new() -> Self {
+        Tutor {
+            top_row: Vec::new(),
+            pattern: String::new(),
+        }
+    }
+
+    // Ticks off a top-row press, or drops the top-row presses when the player strays from them.
+    fn pressed(&mut self, point: &Point) {
+        match self.top_row.iter().position(|press| press == point) {
+            Some(index) => {
+                self.top_row.remove(index);
+            }
+            None => self.top_row.clear(),
+        }
+    }
+
+    fn forget(&mut self) {
+        self.top_row.clear();
+    }
+
+    // The button to press next and what to tell the player about it.
+    fn next_step(&mut self, game: &Game) -> (Option<Point>, String) {
+        if let Some(&next) = self.top_row.first() {
+            return (Some(next), self.top_row_message());
+        }
+
+        let bottom = game.height() - 1;
+        let lit = |point: &Point| game.square(point) != game.target_square(point);
+        if let Some(light) = game
+            .cells()
+            .into_iter()
+            .find(|point| point.y < bottom && lit(point))
+        {
+            return (
+                Some(Point::new(light.x, light.y + 1)),
+                format!(
+                    "Chase the lights: press under the light on in row {}",
+                    light.y + 1
+                ),
+            );
+        }
+
+        self.pattern = (0..game.width())
+            .map(|x| {
+                if lit(&Point::new(x, bottom)) {
+                    '#'
+                } else {
+                    '.'
+                }
+            })
+            .collect();
+        match solve_by_chasing(game) {
+            Ok(solution) => {
+                self.top_row = (0..game.width())
+                    .filter(|&x| solution[0][x as usize] > 0)
+                    .map(|x| Point::new(x, 0))
+                    .collect();
+                (self.top_row.first().copied(), self.top_row_message())
+            }
+            Err(_) => (
+                None,
+                format!(
+                    "Bottom row left as {} cannot be cleared: the board has no solution",
+                    self.pattern
+                ),
+            ),
+        }
+    }
+
+    fn top_row_message(&self) -> String {
+        let columns: Vec<String> = self
+            .top_row
+            .iter()
+            .map(|point| (point.x + 1).to_string())
+            .collect();
+        let plural = if columns.len() == 1 { "" } else { "s" };
+        format!(
+            "Bottom row left as {}: press the top row in column{} {}, then chase again",
+            self.pattern,
+            plural,
+            columns.join(", ")
+        )
+    }
+}
+
+// Plays the game; with `tutor`, every turn marks the next light-chasing press with <> and explains it.
+pub fn game_loop(mut game: Game, tutor: bool) {
+    if let Some(seed) = game.seed() {
+        println!("Seed: {}", seed);
+    }
+    let mut tutor = tutor.then(Tutor::new);
+    let mut current_point = game.center();
+    let mut history = History::new();
+    let mut hint = None;
+    let mut hints_used = 0;
+    let mut status = None;
+    if let Some(tutor) = &mut tutor {
+        let (next, message) = tutor.next_step(&game);
+        hint = next;
+        status = Some(message);
+    }
+    let mut drawn_lines = draw(
+        &game,
+        &current_point,
+        hint.as_ref(),
+        status.take().as_deref(),
+    );
     loop {
         terminal::enable_raw_mode().expect("Failed to enable raw mode");
         let event = read().unwrap();
-        match event {
-            Event::Key(KeyEvent {
-                code,
-                kind: KeyEventKind::Press,
-                ..
-            }) => match code {
+        if let Event::Key(KeyEvent {
+            code,
+            modifiers,
+            kind: KeyEventKind::Press,
+            ..
+        }) = event
+        {
+            match code {
+                KeyCode::Char('r') if modifiers.contains(KeyModifiers::CONTROL) => {
+                    match history.redo(&mut game) {
+                        Some(point) => {
+                            current_point = point;
+                            hint = None;
+                            if let Some(tutor) = &mut tutor {
+                                tutor.forget();
+                            }
+                        }
+                        None => status = Some("Nothing to redo".to_string()),
+                    }
+                }
+                KeyCode::Char('u') => match history.undo(&mut game) {
+                    Some(point) => {
+                        current_point = point;
+                        hint = None;
+                        if let Some(tutor) = &mut tutor {
+                            tutor.forget();
+                        }
+                    }
+                    None => status = Some("Nothing to undo".to_string()),
+                },
+                KeyCode::Char('h') if tutor.is_some() => {}
+                KeyCode::Char('h') => {
+                    if hint.is_none() {
+                        hint = find_hint(&game, &current_point);
+                        if hint.is_some() {
+                            hints_used += 1;
+                        }
+                    }
+                    status = Some(match hint {
+                        Some(_) => "Hint: press the light marked <>".to_string(),
+                        None => "No hint available".to_string(),
+                    });
+                }
                 KeyCode::Char('q') => {
                     terminal::disable_raw_mode().expect("Failed to disable raw mode");
                     println!("Quitting...");
                     std::process::exit(0);
                 }
-                KeyCode::Up => {
-                    if current_point.valid_up() {
-                        current_point.y = current_point.y - 1;
-                    } else {
-                        current_point.y = GRID_SIZE - 1;
-                    }
+                KeyCode::Char('w') => {
+                    terminal::disable_raw_mode().expect("Failed to disable raw mode");
+                    status = Some(save_to_file(&game));
                 }
-                KeyCode::Down => {
-                    if current_point.valid_down() {
-                        current_point.y = current_point.y + 1;
-                    } else {
-                        current_point.y = 0;
-                    }
+                KeyCode::Up | KeyCode::Down | KeyCode::Left | KeyCode::Right => {
+                    let (dx, dy) = direction(&game, code, modifiers);
+                    current_point = move_cursor(&game, current_point, dx, dy);
                 }
-                KeyCode::Left => {
-                    if current_point.valid_left() {
-                        current_point.x = current_point.x - 1;
-                    } else {
-                        current_point.x = GRID_SIZE - 1;
-                    }
+                KeyCode::Enter if !game.can_press(&current_point) => {
+                    status = Some("Only lit lights can be pressed".to_string());
                 }
-                KeyCode::Right => {
-                    if current_point.valid_right() {
-                        current_point.x = current_point.x + 1;
-                    } else {
-                        current_point.x = 0;
+                KeyCode::Enter => {
+                    history.press(&mut game, current_point);
+                    hint = None;
+                    if let Some(tutor) = &mut tutor {
+                        tutor.pressed(&current_point);
                     }
                 }
+                _ => {}
+            }
+        }
+        terminal::disable_raw_mode().expect("Failed to disable raw mode");
+        refresh_display(drawn_lines);
+        if game.solved() {
+            display_board(&game);
+            println!("Solved in {} moves", history.move_count());
+            if let Some(shortest_solution) = game.shortest_solution() {
+                println!("Best solve: {} moves", shortest_solution);
+            }
+            if hints_used > 0 {
+                println!("Hints used: {}", hints_used);
+            }
+            break;
+        }
+        if let Some(tutor) = &mut tutor {
+            let (next, message) = tutor.next_step(&game);
+            hint = next;
+            status = status.or(Some(message));
+        }
+        drawn_lines = draw(
+            &game,
+            &current_point,
+            hint.as_ref(),
+            status.take().as_deref(),
+        );
+    }
+}
+
+// Plays a graph game, reading the node to press as a typed id since graphs have no grid to move around.
+pub fn graph_game_loop(mut game: GraphGame) {
+    let mut moves = 0;
+    let mut status: Option<String> = None;
+    loop {
+        let mut drawn_lines = display_graph(&game);
+        if let Some(status) = status.take() {
+            println!("{}", status);
+            drawn_lines += 1;
+        }
+        let input = prompt("Node to press ('q' to quit): ");
+        let input = input.trim();
+        if input == "q" {
+            println!("Quitting...");
+            std::process::exit(0);
+        }
+        match input.parse::<usize>() {
+            Ok(node) if node < game.graph().node_count() => {
+                game.press(node);
+                moves += 1;
+            }
+            _ => status = Some(format!("Invalid node: '{}'", input)),
+        }
+        refresh_display(drawn_lines);
+
+        if game.solved() {
+            display_graph(&game);
+            println!("Solved in {} moves", moves);
+            if let Some(shortest_solution) = game.shortest_solution() {
+                println!("Best solve: {} moves", shortest_solution);
+            }
+            break;
+        }
+    }
+}
+
+pub fn setup(mut game: Game) -> Game {
+    let mut current_point = game.center();
+    println!("Press 'S' to save and continue, 'W' to write the board to a file or 'Q' to quit");
+    let mut status = None;
+    let mut drawn_lines = draw(&game, &current_point, None, None);
+    loop {
+        terminal::enable_raw_mode().expect("Failed to enable raw mode");
+        let event = read().unwrap();
+        if let Event::Key(KeyEvent {
+            code,
+            modifiers,
+            kind: KeyEventKind::Press,
+            ..
+        }) = event
+        {
+            match code {
+                KeyCode::Char('q') => {
+                    terminal::disable_raw_mode().expect("Failed to disable raw mode");
+                    println!("Quitting...");
+                    std::process::exit(0);
+                }
+                KeyCode::Char('s') => {
+                    terminal::disable_raw_mode().expect("Failed to disable raw mode");
+                    refresh_display(drawn_lines);
+                    break;
+                }
+                KeyCode::Char('w') => {
+                    terminal::disable_raw_mode().expect("Failed to disable raw mode");
+                    status = Some(save_to_file(&game));
+                }
+                KeyCode::Up | KeyCode::Down | KeyCode::Left | KeyCode::Right => {
+                    let (dx, dy) = direction(&game, code, modifiers);
+                    current_point = move_cursor(&game, current_point, dx, dy);
+                }
                 KeyCode::Enter => {
                     terminal::disable_raw_mode().expect("Failed to disable raw mode");
-                    game.toggle_light(&current_point);
-                    moves += 1;
-                    if game.solved() {
-                        refresh_display(GRID_SIZE);
-                        game.display();
-                        println!("Solved in {} moves", moves);
-                        if game.shortest_solution.is_some() {
-                            println!("Best solve: {} moves", game.shortest_solution.unwrap());
-                        }
-                        break;
-                    }
+                    game.toggle_single_light(&current_point);
                 }
                 _ => {}
-            },
-            _ => {}
+            }
         }
         terminal::disable_raw_mode().expect("Failed to disable raw mode");
-        refresh_display(GRID_SIZE);
-        game.display_with_selector(&current_point);
+        refresh_display(drawn_lines);
+        drawn_lines = draw(&game, &current_point, None, status.take().as_deref());
     }
+    refresh_display(1);
+
+    game
 }
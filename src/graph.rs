@@ -0,0 +1,311 @@
+//! Lights Out on an arbitrary graph, where pressing a node toggles it and the nodes it is joined to.
+//!
+//! Graphs are loaded from a plain-text edge list:
+//!
+//! ```text
+//! ; a 4-cycle with a tail
+//! nodes: 5
+//! lights: 1 0 1 0 0
+//! 0 1
+//! 1 2
+//! 2 3
+//! 3 0
+//! 3: 4
+//! ```
+//!
+//! Each line after the header is either an edge `a b` between two node ids, or an adjacency list `a: b c d`
+//! joining `a` to every node after the colon. Nodes are numbered from 0. Header lines are `key: value` pairs:
+//!
+//! - `nodes`: the number of nodes. Without it, the graph has just enough nodes for the highest id in the edges.
+//! - `states`: how many states each light cycles through, 2 (the default) for classic on/off lights.
+//! - `lights`: the state of every node in order, separated by spaces. Without it, every light starts off.
+//! - `variant`: `sigma` for a game where pressing a node toggles only the nodes it is joined to, not itself,
+//!   or `sigma-plus` for the classic game (the default).
+//!
+//! Blank lines and `;` comments may appear anywhere.
+
+use crate::file::{error, LoadError, ParseError};
+use crate::variant::{parse_states, Neighbourhood, MAX_STATES};
+use crate::Square;
+use std::fs;
+use std::path::Path;
+
+/// The nodes each node's press toggles. Edges join nodes both ways, and every node toggles itself
+/// unless the graph was made for the sigma game.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Graph {
+    toggles: Vec<Vec<usize>>,
+}
+
+impl Graph {
+    /// Creates a graph of `nodes` nodes with no edges, so each press only toggles the pressed node.
+    pub fn new(nodes: usize) -> Self {
+        Graph {
+            toggles: (0..nodes).map(|node| vec![node]).collect(),
+        }
+    }
+
+    /// Creates a graph from the nodes each node's press toggles, which need not include the node itself or be symmetric.
+    /// This is how grid boards, whose press patterns can be lopsided, are turned into graphs.
+    pub fn from_toggles(toggles: Vec<Vec<usize>>) -> Self {
+        Graph { toggles }
+    }
+
+    /// Creates a graph of `nodes` nodes with no edges, with presses toggling the pressed node only in a closed neighbourhood.
+    pub fn with_neighbourhood(nodes: usize, neighbourhood: Neighbourhood) -> Self {
+        match neighbourhood {
+            Neighbourhood::Closed => Graph::new(nodes),
+            Neighbourhood::Open => Graph {
+                toggles: vec![Vec::new(); nodes],
+            },
+        }
+    }
+
+    /// Joins `a` and `b`, so pressing either toggles the other.
+    pub fn add_edge(&mut self, a: usize, b: usize) {
+        if !self.toggles[a].contains(&b) {
+            self.toggles[a].push(b);
+        }
+        if !self.toggles[b].contains(&a) {
+            self.toggles[b].push(a);
+        }
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.toggles.len()
+    }
+
+    /// The nodes toggled by pressing `node`.
+    pub fn toggled_by(&self, node: usize) -> &[usize] {
+        &self.toggles[node]
+    }
+
+    /// The nodes `node` is joined to, leaving out the node itself.
+    pub fn neighbours(&self, node: usize) -> Vec<usize> {
+        self.toggles[node]
+            .iter()
+            .copied()
+            .filter(|&other| other != node)
+            .collect()
+    }
+}
+
+/// A game of Lights Out played on a graph.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GraphGame {
+    graph: Graph,
+    lights: Vec<Square>,
+    states: u8,
+    shortest_solution: Option<i32>,
+}
+
+impl GraphGame {
+    /// Creates a game on `graph` with every light off, each cycling through `states` states, from 2 up to
+    /// `MAX_STATES`.
+    pub fn new(graph: Graph, states: u8) -> Self {
+        assert!(
+            (2..=MAX_STATES).contains(&states),
+            "lights must have from 2 to {} states",
+            MAX_STATES
+        );
+        GraphGame {
+            lights: vec![Square::OFF; graph.node_count()],
+            graph,
+            states,
+            shortest_solution: None,
+        }
+    }
+
+    pub fn graph(&self) -> &Graph {
+        &self.graph
+    }
+
+    pub fn lights(&self) -> &[Square] {
+        &self.lights
+    }
+
+    pub fn states(&self) -> u8 {
+        self.states
+    }
+
+    pub fn square(&self, node: usize) -> Square {
+        self.lights[node]
+    }
+
+    /// Sets the light at `node`, whose state must be below the game's number of states.
+    pub fn set_square(&mut self, node: usize, square: Square) {
+        assert!(
+            square.state() < self.states,
+            "lights must be in states below the {} states given",
+            self.states
+        );
+        self.lights[node] = square;
+    }
+
+    /// Returns the number of presses in the optimal solution, if it has been calculated.
+    pub fn shortest_solution(&self) -> Option<i32> {
+        self.shortest_solution
+    }
+
+    /// Solves the current graph and stores the optimal number of presses.
+    pub fn calculate_shortest_solution(&mut self) -> Result<i32, crate::SolveError> {
+        let solution = crate::solver::solve_graph(self)?;
+        let presses = solution.iter().sum();
+        self.shortest_solution = Some(presses);
+
+        Ok(presses)
+    }
+
+    /// Presses `node`, advancing every node in its `toggled_by` to the next state.
+    pub fn press(&mut self, node: usize) {
+        for &other in self.graph.toggled_by(node) {
+            self.lights[other] = self.lights[other].next(self.states);
+        }
+    }
+
+    pub fn solved(&self) -> bool {
+        self.lights.iter().all(|light| !light.to_boolean())
+    }
+}
+
+fn parse_node(value: &str, line: usize) -> Result<usize, ParseError> {
+    value
+        .parse()
+        .map_err(|_| error(line, format!("invalid node '{}', expected a number", value)))
+}
+
+/// Parses a graph game in the edge-list format described in the module documentation.
+pub fn parse_graph(input: &str) -> Result<GraphGame, ParseError> {
+    let mut nodes = None;
+    let mut states = 2;
+    let mut neighbourhood = Neighbourhood::default();
+    let mut lights: Option<(usize, Vec<u8>)> = None;
+    let mut edges = Vec::new();
+    let mut last_line = 0;
+
+    for (index, line) in input.lines().enumerate() {
+        let number = index + 1;
+        let line = line.trim();
+        last_line = number;
+
+        if line.is_empty() || line.starts_with(';') {
+            continue;
+        }
+
+        if let Some((key, value)) = line.split_once(':') {
+            let key = key.trim();
+            let value = value.trim();
+            match key.to_lowercase().as_str() {
+                "nodes" => nodes = Some(parse_node(value, number)?),
+                "variant" => {
+                    neighbourhood = match value.to_lowercase().as_str() {
+                        "sigma" => Neighbourhood::Open,
+                        "sigma-plus" | "classic" => Neighbourhood::Closed,
+                        _ => {
+                            return Err(error(
+                                number,
+                                format!(
+                                    "unknown variant '{}', expected 'sigma' or 'sigma-plus'",
+                                    value
+                                ),
+                            ))
+                        }
+                    };
+                }
+                "states" => {
+                    states = parse_states(value).map_err(|message| error(number, message))?;
+                }
+                "lights" => {
+                    let values = value
+                        .split_whitespace()
+                        .map(|light| {
+                            light.parse().map_err(|_| {
+                                error(
+                                    number,
+                                    format!("invalid light '{}', expected a number", light),
+                                )
+                            })
+                        })
+                        .collect::<Result<Vec<u8>, ParseError>>()?;
+                    lights = Some((number, values));
+                }
+                _ if key.chars().all(|character| character.is_ascii_digit()) => {
+                    let node = parse_node(key, number)?;
+                    for neighbour in value.split_whitespace() {
+                        edges.push((number, node, parse_node(neighbour, number)?));
+                    }
+                }
+                key => return Err(error(number, format!("unknown header '{}'", key))),
+            }
+            continue;
+        }
+
+        let ends: Vec<&str> = line.split_whitespace().collect();
+        let [a, b] = ends[..] else {
+            return Err(error(
+                number,
+                format!("expected an edge 'a b', found '{}'", line),
+            ));
+        };
+        edges.push((number, parse_node(a, number)?, parse_node(b, number)?));
+    }
+
+    let highest = edges.iter().map(|&(_, a, b)| a.max(b) + 1).max();
+    let nodes = match (nodes, highest) {
+        (Some(nodes), _) => nodes,
+        (None, Some(highest)) => highest,
+        (None, None) => lights.as_ref().map_or(0, |(_, values)| values.len()),
+    };
+    if nodes == 0 {
+        return Err(error(
+            last_line.max(1),
+            "the graph has no nodes".to_string(),
+        ));
+    }
+
+    let mut graph = Graph::with_neighbourhood(nodes, neighbourhood);
+    for (line, a, b) in edges {
+        if a >= nodes || b >= nodes {
+            return Err(error(
+                line,
+                format!(
+                    "edge {} {} names a node past the last node, {}",
+                    a,
+                    b,
+                    nodes - 1
+                ),
+            ));
+        }
+        graph.add_edge(a, b);
+    }
+
+    let mut game = GraphGame::new(graph, states);
+    if let Some((line, values)) = lights {
+        if values.len() != nodes {
+            return Err(error(
+                line,
+                format!("{} lights given for {} nodes", values.len(), nodes),
+            ));
+        }
+        for (node, state) in values.into_iter().enumerate() {
+            if state >= states {
+                return Err(error(
+                    line,
+                    format!(
+                        "node {} is state {}, but lights only have {} states",
+                        node, state, states
+                    ),
+                ));
+            }
+            game.set_square(node, Square::new(state));
+        }
+    }
+
+    Ok(game)
+}
+
+pub fn load_graph(path: impl AsRef<Path>) -> Result<GraphGame, LoadError> {
+    let input = fs::read_to_string(path)?;
+
+    Ok(parse_graph(&input)?)
+}
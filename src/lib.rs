@@ -0,0 +1,607 @@
+//! Lights Out puzzle engine: board state, moves, random generation and an optimal solver.
+//!
+//! The `terminal-lights-out` binary is a terminal front end over this crate.
+
+pub mod difficulty;
+pub mod file;
+pub mod graph;
+pub mod shape;
+pub mod solver;
+pub mod target;
+pub mod variant;
+
+use rand::{seq::index, Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use std::fmt;
+use variant::MAX_STATES;
+
+pub use difficulty::Difficulty;
+pub use graph::{Graph, GraphGame};
+pub use shape::Shape;
+pub use solver::{
+    can_chase, set_cache_directory, solve_by_chasing, solve_by_search, solve_graph,
+    solve_lights_out, solve_lit_only, SolveError,
+};
+pub use target::Target;
+pub use variant::{Grid, Neighbourhood, Pattern, Restriction, Topology, Variant};
+
+pub const DEFAULT_SIZE: i32 = 5;
+
+pub type Board = Vec<Vec<Square>>;
+pub type NumberBoard = Vec<Vec<i32>>;
+/// Which cells of a board exist: `true` for a playable light, `false` for a hole.
+pub type Mask = Vec<Vec<bool>>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Game {
+    width: i32,
+    height: i32,
+    board: Board,
+    mask: Mask,
+    target: Board,
+    variant: Variant,
+    seed: Option<u64>,
+    shortest_solution: Option<i32>,
+}
+
+/// How `Game::generate_board` scrambles a board.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Generation {
+    /// Presses each button a uniformly random number of times (0 or 1 for classic lights). Every board
+    /// reachable by pressing buttons is a sum of such presses, so this samples uniformly from all solvable boards.
+    #[default]
+    UniformSolvable,
+    /// Applies 10 to 30 presses at random cells, which favours boards with few lights on.
+    RandomPresses,
+    /// Presses a random set of cells and keeps the board only if its optimal solution
+    /// length falls in the difficulty's range of the lengths the board can reach.
+    Difficulty(Difficulty),
+}
+
+// Generation is retried until the board differs from the target; this bounds the retries
+// for rule sets where pressing buttons might not be able to light anything.
+const MAX_GENERATION_ATTEMPTS: usize = 64;
+
+// Boards are drawn until one matches the difficulty, giving up after this many attempts.
+const MAX_DIFFICULTY_ATTEMPTS: usize = 500;
+
+// Random boards are solved to find the longest optimal solution a board can have, which difficulties are measured
+// against: up to this many on small boards, and on large ones, whose lengths vary less, only as many as keep the
+// cells solved in all near `LONGEST_SOLUTION_CELLS`.
+const LONGEST_SOLUTION_SAMPLES: usize = 256;
+const LONGEST_SOLUTION_CELLS: usize = 25_600;
+
+/// Why `Game::generate_board` could not generate a board.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GenerationError {
+    /// No board that is not already solved turned up, as happens when no press changes the board, such as on
+    /// a single light in the sigma game, or on a board that is all holes.
+    AlwaysSolved,
+    /// No board whose optimal solution suits the difficulty turned up.
+    Difficulty(Difficulty),
+}
+
+impl fmt::Display for GenerationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GenerationError::AlwaysSolved => write!(
+                f,
+                "No board that is not already solved can be generated with these rules"
+            ),
+            GenerationError::Difficulty(difficulty) => write!(
+                f,
+                "No {} board could be generated with these rules",
+                difficulty.name().to_lowercase()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for GenerationError {}
+
+// Whether every light of `board` is in one of `states` states.
+fn fits_states(board: &Board, states: u8) -> bool {
+    board.iter().flatten().all(|square| square.state() < states)
+}
+
+/// Picks a fresh seed for `Game::generate_board`.
+pub fn random_seed() -> u64 {
+    rand::random()
+}
+
+impl Game {
+    /// Creates a `width` x `height` game with every light off, played by the given rules, or `None` unless the
+    /// board is at least 1x1, the lights have from 2 up to `MAX_STATES` states and the rules suit the board's
+    /// height (see `Variant::check_height`).
+    pub fn with_variant(width: i32, height: i32, variant: Variant) -> Option<Self> {
+        if !(2..=MAX_STATES).contains(&variant.states) || variant.check_height(height).is_err() {
+            return None;
+        }
+        let mut game = Game::new(width, height)?;
+        game.variant = variant;
+
+        Some(game)
+    }
+
+    /// Creates a `width` x `height` game with every light off, or `None` unless the board is at least 1x1.
+    pub fn new(width: i32, height: i32) -> Option<Self> {
+        if width < 1 || height < 1 {
+            return None;
+        }
+
+        Some(Self {
+            width,
+            height,
+            board: vec![vec![Square::OFF; width as usize]; height as usize],
+            mask: vec![vec![true; width as usize]; height as usize],
+            target: vec![vec![Square::OFF; width as usize]; height as usize],
+            variant: Variant::default(),
+            seed: None,
+            shortest_solution: None,
+        })
+    }
+
+    /// Creates a game from an existing board, returning `None` if the board is empty, its rows differ in length or
+    /// a light is in a state past the last of `MAX_STATES`. The lights have two states, or as many as the board's
+    /// highest light needs, e.g. three for a board with a light in state 2.
+    pub fn from_board(board: Board) -> Option<Self> {
+        let width = board.first()?.len();
+        if width == 0 || board.iter().any(|row| row.len() != width) {
+            return None;
+        }
+        let highest = board.iter().flatten().map(Square::state).max().unwrap_or(0);
+        if highest >= MAX_STATES {
+            return None;
+        }
+
+        Some(Self {
+            width: width as i32,
+            height: board.len() as i32,
+            mask: vec![vec![true; width]; board.len()],
+            target: vec![vec![Square::OFF; width]; board.len()],
+            board,
+            variant: Variant {
+                states: (highest + 1).max(2),
+                ..Variant::default()
+            },
+            seed: None,
+            shortest_solution: None,
+        })
+    }
+
+    pub fn width(&self) -> i32 {
+        self.width
+    }
+
+    pub fn height(&self) -> i32 {
+        self.height
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    pub fn variant(&self) -> &Variant {
+        &self.variant
+    }
+
+    /// Replaces the rules. The lights must have from 2 up to `MAX_STATES` states, enough for every light of the board
+    /// and target, and the rules must suit the board's height (see `Variant::check_height`).
+    pub fn set_variant(&mut self, variant: Variant) {
+        assert!(
+            (2..=MAX_STATES).contains(&variant.states),
+            "lights must have from 2 to {} states",
+            MAX_STATES
+        );
+        assert!(
+            fits_states(&self.board, variant.states) && fits_states(&self.target, variant.states),
+            "lights must be in states below the {} states given",
+            variant.states
+        );
+        if let Err(message) = variant.check_height(self.height) {
+            panic!("{}", message);
+        }
+        self.variant = variant;
+    }
+
+    pub fn square(&self, point: &Point) -> Square {
+        self.board[point.y as usize][point.x as usize]
+    }
+
+    pub fn mask(&self) -> &Mask {
+        &self.mask
+    }
+
+    /// Replaces the board's shape, turning off any light, or light of the target, that falls in a hole.
+    /// The mask must be the same size as the board.
+    pub fn set_mask(&mut self, mask: Mask) {
+        assert!(
+            mask.len() == self.height as usize
+                && mask.iter().all(|row| row.len() == self.width as usize),
+            "mask must be the same size as the board"
+        );
+        self.mask = mask;
+        for board in [&mut self.board, &mut self.target] {
+            for (row, present) in board.iter_mut().zip(&self.mask) {
+                for (square, &present) in row.iter_mut().zip(present) {
+                    if !present {
+                        *square = Square::OFF;
+                    }
+                }
+            }
+        }
+    }
+
+    /// The picture the board must match to be solved, every light off unless set otherwise.
+    pub fn target(&self) -> &Board {
+        &self.target
+    }
+
+    /// Replaces the picture the board must match to be solved. Cells in holes are turned off.
+    /// The target must be the same size as the board, with every light in one of the game's states.
+    pub fn set_target(&mut self, target: Board) {
+        assert!(
+            target.len() == self.height as usize
+                && target.iter().all(|row| row.len() == self.width as usize),
+            "target must be the same size as the board"
+        );
+        assert!(
+            fits_states(&target, self.variant.states),
+            "lights must be in states below the {} states given",
+            self.variant.states
+        );
+        self.target = target;
+        self.set_mask(self.mask.clone());
+    }
+
+    pub fn target_square(&self, point: &Point) -> Square {
+        self.target[point.y as usize][point.x as usize]
+    }
+
+    /// Whether the goal is a picture, rather than every light off.
+    pub fn has_target(&self) -> bool {
+        self.target.iter().flatten().any(Square::to_boolean)
+    }
+
+    /// Whether the cell at `point` is a light, rather than a hole in the board.
+    pub fn is_present(&self, point: &Point) -> bool {
+        self.mask[point.y as usize][point.x as usize]
+    }
+
+    /// Every cell that is a light, leaving out holes, in row-major order.
+    pub fn cells(&self) -> Vec<Point> {
+        let mut cells = Vec::new();
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let point = Point::new(x, y);
+                if self.is_present(&point) {
+                    cells.push(point);
+                }
+            }
+        }
+
+        cells
+    }
+
+    /// The light closest to the middle of the board, where the cursor starts.
+    pub fn center(&self) -> Point {
+        let center = Point::new(self.width / 2, self.height / 2);
+        self.cells()
+            .into_iter()
+            .min_by_key(|point| (point.x - center.x).abs() + (point.y - center.y).abs())
+            .unwrap_or(center)
+    }
+
+    /// The seed the board was generated from, if it was generated or loaded from a file that recorded it.
+    pub fn seed(&self) -> Option<u64> {
+        self.seed
+    }
+
+    pub fn set_seed(&mut self, seed: Option<u64>) {
+        self.seed = seed;
+    }
+
+    /// The minimum number of presses needed to solve the board, once calculated.
+    pub fn shortest_solution(&self) -> Option<i32> {
+        self.shortest_solution
+    }
+
+    /// Solves the current board and remembers the minimum number of presses needed, by light chasing where
+    /// it works so that very large boards are quick. Lit-only boards are searched for the shortest press order
+    /// that only ever presses lit lights.
+    pub fn calculate_shortest_solution(&mut self) -> Result<i32, SolveError> {
+        let presses = match self.variant.restriction {
+            Restriction::None => solve_by_chasing(self)?.iter().flatten().sum(),
+            Restriction::LitOnly => solve_lit_only(self)?.len() as i32,
+        };
+        self.shortest_solution = Some(presses);
+
+        Ok(presses)
+    }
+
+    /// Whether the light at `point` may be pressed: any light, or only lit ones in the lit-only variant.
+    pub fn can_press(&self, point: &Point) -> bool {
+        self.is_present(point)
+            && (self.variant.restriction == Restriction::None || self.square(point).to_boolean())
+    }
+
+    /// Replaces the board with a random solvable one that is not already solved, or returns an error when no
+    /// such board turns up, leaving the board solved.
+    ///
+    /// The presses come from a ChaCha8 generator seeded with `seed`, so the same seed, board
+    /// size and generation method always produce the same board, on every run and every machine.
+    pub fn generate_board(
+        &mut self,
+        seed: u64,
+        generation: Generation,
+    ) -> Result<(), GenerationError> {
+        self.seed = Some(seed);
+        self.generate_board_with(&mut ChaCha8Rng::seed_from_u64(seed), generation)
+    }
+
+    /// Replaces the board with a random solvable one drawn from `rand`, so a seeded generator reproduces the same board.
+    pub fn generate_board_with(
+        &mut self,
+        rand: &mut impl Rng,
+        generation: Generation,
+    ) -> Result<(), GenerationError> {
+        if let Generation::Difficulty(difficulty) = generation {
+            return self.generate_board_for_difficulty(rand, difficulty);
+        }
+
+        for _ in 0..MAX_GENERATION_ATTEMPTS {
+            self.reset_to_target();
+            match generation {
+                Generation::UniformSolvable => {
+                    for point in self.cells() {
+                        for _ in 0..rand.gen_range(0..self.variant.states) {
+                            self.toggle_light(&point);
+                        }
+                    }
+                }
+                Generation::Difficulty(_) => unreachable!("handled above"),
+                Generation::RandomPresses => {
+                    let moves = rand.gen_range(10..30);
+                    let cells = self.cells();
+
+                    for _ in 0..moves {
+                        if cells.is_empty() {
+                            break;
+                        }
+                        // A usize is sampled at the platform's width, so 32-bit and 64-bit machines would
+                        // draw different cells from the same seed; a u32 is the same everywhere
+                        let point = cells[rand.gen_range(0..cells.len() as u32) as usize];
+
+                        self.toggle_light(&point);
+                    }
+                }
+            }
+
+            if !self.solved() && self.lit_only_solvable() {
+                return Ok(());
+            }
+        }
+
+        self.reset_to_target();
+        Err(GenerationError::AlwaysSolved)
+    }
+
+    // Lit-only boards may be solvable but not in any order that presses only lit lights; boards too large
+    // to search are accepted as they are.
+    fn lit_only_solvable(&self) -> bool {
+        self.variant.restriction == Restriction::None
+            || !matches!(solve_lit_only(self), Err(SolveError::LitOnlyUnsolvable))
+    }
+
+    // Presses a random set of cells whose size is in the difficulty's range, so the optimal solution
+    // is at most that long, then checks the actual optimum with the solver. Lit-only boards are rated
+    // by their unrestricted optimum, as searching every press order for each candidate would be too slow.
+    fn generate_board_for_difficulty(
+        &mut self,
+        rand: &mut impl Rng,
+        difficulty: Difficulty,
+    ) -> Result<(), GenerationError> {
+        let cells = self.cells();
+        let longest = self.longest_solution();
+        if longest == 0 {
+            // No press changes the board, as when it is all holes
+            self.reset_to_target();
+            return Err(GenerationError::AlwaysSolved);
+        }
+        let range = difficulty.press_range(longest);
+        let longest = longest.max(*range.start());
+
+        for _ in 0..MAX_DIFFICULTY_ATTEMPTS {
+            self.reset_to_target();
+            // Each cell is pressed at most `states - 1` times, the most any optimal solution presses it
+            let presses = rand.gen_range(*range.start()..=longest.min(*range.end()));
+            let slots = cells.len() * (self.variant.states as usize - 1);
+            for slot in index::sample(rand, slots, presses as usize) {
+                self.toggle_light(&cells[slot % cells.len()]);
+            }
+
+            let Ok(solution) = solve_by_chasing(self) else {
+                continue;
+            };
+            let shortest_solution = solution.iter().flatten().sum();
+            if shortest_solution == 0 || !self.lit_only_solvable() {
+                continue;
+            }
+            if range.contains(&shortest_solution) {
+                return Ok(());
+            }
+        }
+
+        self.reset_to_target();
+        Err(GenerationError::Difficulty(difficulty))
+    }
+
+    /// The longest optimal solution among a fixed sample of random solvable boards with this size, shape,
+    /// rules and target, or 0 when no press changes the board. A press changes the optimal solution's length
+    /// by at most one, so every length up to this one can be reached; difficulty ranges are measured against it.
+    pub fn longest_solution(&self) -> i32 {
+        let mut rand = ChaCha8Rng::seed_from_u64(0);
+        let mut sample = self.clone();
+        let samples =
+            (LONGEST_SOLUTION_CELLS / self.cells().len().max(1)).clamp(8, LONGEST_SOLUTION_SAMPLES);
+
+        (0..samples)
+            .filter_map(|_| {
+                sample.reset_to_target();
+                for point in sample.cells() {
+                    for _ in 0..rand.gen_range(0..sample.variant.states) {
+                        sample.toggle_light(&point);
+                    }
+                }
+                solve_by_chasing(&sample)
+                    .ok()
+                    .map(|solution| solution.iter().flatten().sum())
+            })
+            .max()
+            .unwrap_or(0)
+    }
+
+    // Generated boards start from the solved picture, so any presses on top of it can be undone.
+    fn reset_to_target(&mut self) {
+        self.board = self.target.clone();
+    }
+
+    /// The cells toggled by pressing the button at `point`, as given by the variant's pattern or hex neighbourhood,
+    /// leaving out `point` itself in the sigma game.
+    /// Each cell is listed once, even if the pattern reaches it more than once on a small torus.
+    /// Holes are never toggled, and pressing a hole toggles nothing.
+    pub fn toggled_by(&self, point: &Point) -> Vec<Point> {
+        let mut points = Vec::new();
+        if !self.is_present(point) {
+            return points;
+        }
+        for (dx, dy) in self.variant.neighbourhood() {
+            let dx = self.variant.grid.column_offset(point.y, dx, dy);
+            if let Some(neighbour) = self.offset(point, dx, dy) {
+                if self.is_present(&neighbour) && !points.contains(&neighbour) {
+                    points.push(neighbour);
+                }
+            }
+        }
+
+        points
+    }
+
+    // Moves `point` by (dx, dy), wrapping around on a torus. Returns None when it leaves a bounded board.
+    fn offset(&self, point: &Point, dx: i32, dy: i32) -> Option<Point> {
+        let x = point.x + dx;
+        let y = point.y + dy;
+        match self.variant.topology {
+            Topology::Bounded => {
+                if (0..self.width).contains(&x) && (0..self.height).contains(&y) {
+                    Some(Point::new(x, y))
+                } else {
+                    None
+                }
+            }
+            Topology::Torus => Some(Point::new(
+                x.rem_euclid(self.width),
+                y.rem_euclid(self.height),
+            )),
+        }
+    }
+
+    /// The board as a graph with a node per light, numbered in the order of `cells`, where each
+    /// node toggles the cells in its `toggled_by`. Holes are left out of the graph.
+    pub fn graph(&self) -> Graph {
+        let cells = self.cells();
+        let mut nodes = vec![vec![0; self.width as usize]; self.height as usize];
+        for (node, cell) in cells.iter().enumerate() {
+            nodes[cell.y as usize][cell.x as usize] = node;
+        }
+        let toggles = cells
+            .iter()
+            .map(|cell| {
+                self.toggled_by(cell)
+                    .iter()
+                    .map(|point| nodes[point.y as usize][point.x as usize])
+                    .collect()
+            })
+            .collect();
+
+        Graph::from_toggles(toggles)
+    }
+
+    /// Presses the button at `point`, advancing every cell in `toggled_by` to its next state.
+    pub fn toggle_light(&mut self, point: &Point) {
+        for point in self.toggled_by(point) {
+            self.toggle_single_light(&point);
+        }
+    }
+
+    /// Advances only the light at `point` to its next state, used when entering a board by hand.
+    pub fn toggle_single_light(&mut self, point: &Point) {
+        if !self.is_present(point) {
+            return;
+        }
+        let square = &mut self.board[point.y as usize][point.x as usize];
+        *square = square.next(self.variant.states);
+    }
+
+    /// Whether every light matches the target, which is every light off unless a picture was set.
+    pub fn solved(&self) -> bool {
+        self.board == self.target
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Point {
+    pub x: i32,
+    pub y: i32,
+}
+
+impl Point {
+    pub fn new(x: i32, y: i32) -> Point {
+        Point { x, y }
+    }
+}
+
+/// A single light. State 0 is off; a press moves a light to the next state, wrapping back to 0
+/// after the last one, so classic two-state lights flip between off (0) and on (1).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Square {
+    state: u8,
+}
+
+impl Square {
+    pub const OFF: Square = Square { state: 0 };
+    pub const ON: Square = Square { state: 1 };
+
+    pub fn new(state: u8) -> Square {
+        Square { state }
+    }
+
+    pub fn state(&self) -> u8 {
+        self.state
+    }
+
+    /// The glyph for this light in a game with `states` states: ○ and ⦿ for classic lights,
+    /// or ○ followed by a numbered circle for each lit state.
+    pub fn to_visual(&self, states: u8) -> String {
+        const NUMBERED: [&str; 20] = [
+            "①", "②", "③", "④", "⑤", "⑥", "⑦", "⑧", "⑨", "⑩", "⑪", "⑫", "⑬", "⑭", "⑮", "⑯", "⑰",
+            "⑱", "⑲", "⑳",
+        ];
+        match self.state {
+            0 => "○".to_string(),
+            1 if states == 2 => "⦿".to_string(),
+            state => NUMBERED
+                .get(state as usize - 1)
+                .map_or_else(|| state.to_string(), |glyph| glyph.to_string()),
+        }
+    }
+
+    pub fn to_boolean(&self) -> bool {
+        self.state != 0
+    }
+
+    /// The state after one press, in a game with `states` states.
+    pub fn next(&self, states: u8) -> Square {
+        Square::new((self.state + 1) % states)
+    }
+}
@@ -1,220 +1,326 @@
+mod cli;
 mod display;
 mod game;
-mod solver;
 
-use display::{welcome, OptionSelect};
-use game::game_loop;
-use rand::Rng;
-use solver::{setup, solve_lights_out};
+use cli::{parse_arguments, run, Arguments, USAGE};
+use display::{
+    display_graph, display_graph_solution, display_press_order, display_solution, prompt, welcome,
+    OptionSelect,
+};
+use game::{can_tutor, game_loop, graph_game_loop, setup, TUTOR_RULES};
+use terminal_lights_out::file::{load_puzzle, parse_size};
+use terminal_lights_out::graph::load_graph;
+use terminal_lights_out::variant::LIGHTS_OUT_2000_STATES;
+use terminal_lights_out::{
+    random_seed, set_cache_directory, solve_graph, solve_lights_out, solve_lit_only, Difficulty,
+    Game, Generation, GraphGame, Pattern, Restriction, Shape, SolveError, Target, Variant,
+    DEFAULT_SIZE,
+};
 
-pub const GRID_SIZE: i32 = 5;
-
-pub type Board = [[Square; GRID_SIZE as usize]; GRID_SIZE as usize];
-pub type NumberBoard = [[i32; GRID_SIZE as usize]; GRID_SIZE as usize];
-
-pub struct Game {
-    board: Board,
-    shortest_solution: Option<i32>,
-}
+fn ask_size() -> (i32, i32) {
+    let size = OptionSelect::new()
+        .set_title("Select board size:")
+        .add_option("3x3")
+        .add_option("5x5")
+        .add_option("7x7")
+        .add_option("10x10")
+        .add_option("Custom")
+        .ask();
 
-impl Game {
-    pub fn new() -> Self {
-        Self {
-            board: [[Square::Off; GRID_SIZE as usize]; GRID_SIZE as usize],
-            shortest_solution: None,
-        }
+    if size != "Custom" {
+        return parse_size(&size).unwrap_or((DEFAULT_SIZE, DEFAULT_SIZE));
     }
 
-    pub fn generate_board(&mut self) {
-        let mut rand = rand::thread_rng();
-        let moves = rand.gen_range(10..30);
-
-        for _ in 0..moves {
-            let x = rand.gen_range(0..GRID_SIZE - 1);
-            let y = rand.gen_range(0..GRID_SIZE - 1);
-
-            self.toggle_light(&Point::new(x, y));
+    loop {
+        let input = prompt("Board size (e.g. 6 or 7x5): ");
+        match parse_size(&input) {
+            Some(size) => return size,
+            None => println!("Invalid board size: '{}'", input.trim()),
         }
     }
+}
 
-    pub fn toggle_light(&mut self, point: &Point) {
-        self.board[point.y as usize][point.x as usize] =
-            Square::opposite(&self.board[point.y as usize][point.x as usize]);
+fn ask_shape() -> Shape {
+    let mut select = OptionSelect::new().set_title("Select shape:");
+    for shape in Shape::ALL {
+        select = select.add_option(shape.name());
+    }
 
-        if point.valid_left() {
-            self.board[point.y as usize][(point.x - 1) as usize] =
-                Square::opposite(&self.board[point.y as usize][(point.x - 1) as usize]);
-        }
+    Shape::from_name(&select.ask()).unwrap_or_default()
+}
 
-        if point.valid_right() {
-            self.board[point.y as usize][(point.x + 1) as usize] =
-                Square::opposite(&self.board[point.y as usize][(point.x + 1) as usize]);
-        }
+fn ask_target() -> Target {
+    let mut select = OptionSelect::new().set_title("Select goal:");
+    for target in Target::ALL {
+        select = select.add_option(target.name());
+    }
 
-        if point.valid_up() {
-            self.board[(point.y - 1) as usize][point.x as usize] =
-                Square::opposite(&self.board[(point.y - 1) as usize][point.x as usize]);
-        }
+    Target::from_name(&select.ask()).unwrap_or_default()
+}
 
-        if point.valid_down() {
-            self.board[(point.y + 1) as usize][point.x as usize] =
-                Square::opposite(&self.board[(point.y + 1) as usize][point.x as usize]);
-        }
+// Sets the picture a new board must match, from --target or the menu.
+fn choose_target(arguments: &Arguments, game: &mut Game) -> Result<(), String> {
+    if arguments.target.is_some() {
+        return arguments.apply_target(game);
     }
+    game.set_target(ask_target().board(game.width(), game.height()));
 
-    pub fn toggle_single_light(&mut self, point: &Point) {
-        self.board[point.y as usize][point.x as usize] =
-            Square::opposite(&self.board[point.y as usize][point.x as usize]);
-    }
+    Ok(())
+}
 
-    pub fn solved(&self) -> bool {
-        for row in self.board {
-            for square in row {
-                if square == Square::On {
-                    return false;
-                }
-            }
-        }
+fn main() {
+    let arguments = parse_arguments(std::env::args().skip(1)).unwrap_or_else(|error| {
+        eprintln!("{}\n\n{}", error, USAGE);
+        std::process::exit(2);
+    });
 
-        true
+    if let Some(directory) = &arguments.cache_dir {
+        set_cache_directory(Some(directory.into()));
     }
 
-    pub fn display_with_selector(&self, point: &Point) {
-        for (y, row) in self.board.iter().enumerate() {
-            for (x, square) in row.iter().enumerate() {
-                if y == point.y as usize && x == point.x as usize {
-                    print!("|{}|", square.to_visual());
-                } else {
-                    print!(" {} ", square.to_visual());
-                }
-            }
-            println!();
-        }
+    if let Some(subcommand) = arguments.subcommand {
+        let code = run(subcommand, &arguments).unwrap_or_else(|error| {
+            eprintln!("{}", error);
+            2
+        });
+        std::process::exit(code);
     }
 
-    pub fn display(&self) {
-        for row in self.board {
-            for square in row {
-                print!(" {} ", square.to_visual());
-            }
-            println!();
-        }
-    }
+    interactive(&arguments);
 }
 
-#[derive(Debug)]
-pub struct Point {
-    x: i32,
-    y: i32,
-}
-
-impl Point {
-    pub fn new(x: i32, y: i32) -> Point {
-        Point { x, y }
-    }
-
-    pub fn valid_left(&self) -> bool {
-        if self.x == 0 {
-            false
-        } else {
-            true
+// Asks for a puzzle file path until one loads successfully.
+fn ask_puzzle_file() -> Game {
+    loop {
+        let path = prompt("Puzzle file: ");
+        match load_puzzle(path.trim()) {
+            Ok(game) => return game,
+            Err(error) => println!("Failed to load '{}': {}", path.trim(), error),
         }
     }
+}
 
-    pub fn valid_right(&self) -> bool {
-        if self.x == GRID_SIZE - 1 {
-            false
-        } else {
-            true
+// Asks for a graph file path until one loads successfully.
+fn ask_graph_file() -> GraphGame {
+    loop {
+        let path = prompt("Graph file: ");
+        match load_graph(path.trim()) {
+            Ok(game) => return game,
+            Err(error) => println!("Failed to load '{}': {}", path.trim(), error),
         }
     }
+}
 
-    pub fn valid_up(&self) -> bool {
-        if self.y == 0 {
-            false
-        } else {
-            true
+// Plays or solves a graph game, which has no grid to set up or edit.
+fn graph_mode(mode: &str, mut game: GraphGame) {
+    if mode == "Play" {
+        match game.calculate_shortest_solution() {
+            Ok(_) => graph_game_loop(game),
+            Err(error) => println!("{}", error),
         }
+        return;
     }
 
-    pub fn valid_down(&self) -> bool {
-        if self.y == GRID_SIZE - 1 {
-            false
-        } else {
-            true
-        }
+    display_graph(&game);
+    match solve_graph(&game) {
+        Ok(solution) => display_graph_solution(&solution),
+        Err(error) => println!("{}", error),
     }
 }
 
-#[derive(Debug, Clone, Copy)]
-pub enum Square {
-    On,
-    Off,
-}
+fn ask_variant() -> Variant {
+    let variant = OptionSelect::new()
+        .set_title("Select rules:")
+        .add_option("Classic")
+        .add_option("Torus (edges wrap around)")
+        .add_option("Hexagonal grid")
+        .add_option("Sigma (only the neighbours toggle)")
+        .add_option("Lit-only (press only lights that are on)")
+        .add_option("Lights Out 2000 (off, red, green)")
+        .add_option("Diagonals (X)")
+        .add_option("3x3 square")
+        .add_option("Knight moves")
+        .add_option("Big plus (two cells each way)")
+        .ask();
 
-impl PartialEq for Square {
-    fn eq(&self, other: &Self) -> bool {
-        match self {
-            Square::On => match other {
-                Square::On => true,
-                Square::Off => false,
-            },
-            Square::Off => match other {
-                Square::Off => true,
-                Square::On => false,
-            },
+    match variant.as_str() {
+        "Torus (edges wrap around)" => Variant::torus(),
+        "Hexagonal grid" => Variant::hex(),
+        "Sigma (only the neighbours toggle)" => Variant::sigma(),
+        "Lit-only (press only lights that are on)" => Variant::lit_only(),
+        "Lights Out 2000 (off, red, green)" => {
+            Variant::multi_state(LIGHTS_OUT_2000_STATES).expect("Lights Out 2000 has 3 states")
         }
+        "Diagonals (X)" => Variant::with_pattern(Pattern::X),
+        "3x3 square" => Variant::with_pattern(Pattern::Square),
+        "Knight moves" => Variant::with_pattern(Pattern::Knight),
+        "Big plus (two cells each way)" => Variant::with_pattern(Pattern::BigPlus),
+        _ => Variant::default(),
     }
 }
 
-impl Square {
-    pub fn to_visual(&self) -> String {
-        match self {
-            Square::Off => "○".to_string(),
-            Square::On => "⦿".to_string(),
-        }
+fn ask_difficulty() -> Generation {
+    let mut select = OptionSelect::new()
+        .set_title("Select difficulty:")
+        .add_option("Any");
+    for difficulty in Difficulty::ALL {
+        select = select.add_option(difficulty.name());
     }
 
-    pub fn to_boolean(&self) -> bool {
-        match self {
-            Square::Off => false,
-            Square::On => true,
-        }
+    match Difficulty::from_name(&select.ask()) {
+        Some(difficulty) => Generation::Difficulty(difficulty),
+        None => Generation::UniformSolvable,
     }
 }
 
-impl Square {
-    pub fn opposite(square: &Square) -> Square {
-        match square {
-            Square::Off => Square::On,
-            Square::On => Square::Off,
+fn ask_seed() -> u64 {
+    loop {
+        let input = prompt("Seed: ");
+        match input.trim().parse() {
+            Ok(seed) => return seed,
+            Err(_) => println!("Invalid seed: '{}', expected a number", input.trim()),
         }
     }
 }
 
-fn main() {
+fn interactive(arguments: &Arguments) {
     welcome();
     let mode = OptionSelect::new()
         .set_title("Select mode:")
         .add_option("Play")
+        .add_option("Tutor")
         .add_option("Solve")
         .ask();
+    // The tutor plays like Play, but on boards light chasing works on
+    let tutor = mode == "Tutor";
+    let source = OptionSelect::new()
+        .set_title("Select board:")
+        .add_option(if mode == "Solve" {
+            "Enter board"
+        } else {
+            "Random board"
+        })
+        .add_option_if(mode != "Solve", "Board from seed")
+        .add_option("Load from file")
+        .add_option_if(!tutor, "Load graph file")
+        .ask();
+    if source == "Load graph file" {
+        graph_mode(&mode, ask_graph_file());
+        return;
+    }
+    let loaded = if source == "Load from file" {
+        let mut game = ask_puzzle_file();
+        if let Err(error) = arguments
+            .override_variant(&mut game)
+            .and_then(|()| arguments.apply_target(&mut game))
+        {
+            println!("{}", error);
+            return;
+        }
+        Some(game)
+    } else {
+        None
+    };
+    let seed = match arguments.seed {
+        None if source == "Board from seed" => Some(ask_seed()),
+        seed => seed,
+    };
 
     match mode.as_str() {
-        "Play" => {
-            let mut game = Game::new();
-            game.generate_board();
-            let point_solution_vector = solve_lights_out(&game.board, false);
-            let mut shortest_solution = 0;
-            point_solution_vector.iter().flatten().for_each(|&point| {
-                shortest_solution += point;
-            });
-            game.shortest_solution = Some(shortest_solution);
-            game_loop(game);
+        "Play" | "Tutor" => {
+            let mut game = match loaded {
+                Some(game) => game,
+                None => {
+                    let (width, height) = arguments.size.unwrap_or_else(ask_size);
+                    // Chasing needs a board without holes, so the tutor does not offer shapes or other rules
+                    let shape = match arguments.shape {
+                        Some(shape) => shape,
+                        None if tutor => Shape::default(),
+                        None => ask_shape(),
+                    };
+                    let variant = match arguments.variant() {
+                        Some(variant) => variant,
+                        None if tutor => Variant::default(),
+                        None => ask_variant(),
+                    };
+                    if let Err(error) = variant.check_height(height) {
+                        println!("{}", error);
+                        return;
+                    }
+                    let mut game = Game::with_variant(width, height, variant)
+                        .expect("the size and rules were checked");
+                    game.set_mask(shape.mask(width, height));
+                    if let Err(error) = choose_target(arguments, &mut game) {
+                        println!("{}", error);
+                        return;
+                    }
+                    let generation = arguments.generation.unwrap_or_else(ask_difficulty);
+                    if let Err(error) =
+                        game.generate_board(seed.unwrap_or_else(random_seed), generation)
+                    {
+                        println!("{}", error);
+                        return;
+                    }
+                    game
+                }
+            };
+            if tutor && !can_tutor(&game) {
+                println!("{}", TUTOR_RULES);
+                return;
+            }
+            match game.calculate_shortest_solution() {
+                Ok(_) | Err(SolveError::SearchTooLarge { .. }) => {}
+                Err(error) => {
+                    println!("{}", error);
+                    return;
+                }
+            }
+            game_loop(game, tutor);
         }
         "Solve" => {
-            let game = setup();
-            solve_lights_out(&game.board, true);
+            let mut game = match loaded {
+                Some(game) => game,
+                None => {
+                    let (width, height) = arguments.size.unwrap_or_else(ask_size);
+                    let shape = arguments.shape.unwrap_or_else(ask_shape);
+                    let variant = arguments.variant().unwrap_or_else(ask_variant);
+                    if let Err(error) = variant.check_height(height) {
+                        println!("{}", error);
+                        return;
+                    }
+                    let mut game = Game::with_variant(width, height, variant)
+                        .expect("the size and rules were checked");
+                    game.set_mask(shape.mask(width, height));
+                    if let Err(error) = choose_target(arguments, &mut game) {
+                        println!("{}", error);
+                        return;
+                    }
+                    setup(game)
+                }
+            };
+            loop {
+                let result = match game.variant().restriction {
+                    Restriction::None => {
+                        solve_lights_out(&game).map(|solution| display_solution(&game, &solution))
+                    }
+                    Restriction::LitOnly => {
+                        solve_lit_only(&game).map(|order| display_press_order(&order))
+                    }
+                };
+                let Err(error) = result else {
+                    break;
+                };
+                println!("{}", error);
+                let choice = OptionSelect::new()
+                    .set_title("What next?")
+                    .add_option("Edit board")
+                    .add_option("Quit")
+                    .ask();
+                if choice == "Quit" {
+                    break;
+                }
+                game = setup(game);
+            }
         }
         _ => panic!("Invalid mode selected"),
     }
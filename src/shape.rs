@@ -0,0 +1,61 @@
+use crate::Mask;
+
+/// Built-in board shapes, cut out of the rectangular board by marking cells as holes.
+/// Any other shape can be drawn in a puzzle file, with `-` for each hole.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Shape {
+    /// Every cell is a light.
+    #[default]
+    Rectangle,
+    /// A plus sign: the middle third of the rows and columns.
+    Cross,
+    /// A diamond touching the middle of each edge.
+    Diamond,
+    /// A frame around a hole in the middle third of the board.
+    Ring,
+}
+
+impl Shape {
+    pub const ALL: [Shape; 4] = [Shape::Rectangle, Shape::Cross, Shape::Diamond, Shape::Ring];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Shape::Rectangle => "rectangle",
+            Shape::Cross => "cross",
+            Shape::Diamond => "diamond",
+            Shape::Ring => "ring",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Shape::ALL
+            .into_iter()
+            .find(|shape| shape.name().eq_ignore_ascii_case(name.trim()))
+    }
+
+    /// The cells of a `width` x `height` board that are lights in this shape.
+    pub fn mask(&self, width: i32, height: i32) -> Mask {
+        let middle = |value: i32, size: i32| (size / 3..size - size / 3).contains(&value);
+
+        (0..height)
+            .map(|y| {
+                (0..width)
+                    .map(|x| match self {
+                        Shape::Rectangle => true,
+                        Shape::Cross => middle(x, width) || middle(y, height),
+                        // Scaled so the diamond reaches the middle of every edge on non-square boards
+                        Shape::Diamond => {
+                            (2 * x - (width - 1)).abs() * height
+                                + (2 * y - (height - 1)).abs() * width
+                                <= width * height
+                        }
+                        // Boards under 3 cells across have no middle third to cut out
+                        Shape::Ring => {
+                            width < 3 || height < 3 || !(middle(x, width) && middle(y, height))
+                        }
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
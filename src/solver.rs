@@ -1,235 +1,325 @@
 // based on https://www.keithschwarz.com/interesting/code/?dir=lights-out
-use super::{Board, Game, Point, GRID_SIZE};
-use crate::display::refresh_display;
-use crossterm::{
-    event::{read, Event, KeyCode, KeyEvent, KeyEventKind},
-    terminal,
-};
-
-pub fn setup() -> Game {
-    let mut game = Game::new();
-    let mut current_point = Point::new(2, 2);
-    println!("Press 'S' to save and continue or 'Q' to quit");
-    game.display_with_selector(&current_point);
-    loop {
-        terminal::enable_raw_mode().expect("Failed to enable raw mode");
-        let event = read().unwrap();
-        match event {
-            Event::Key(KeyEvent {
-                code,
-                kind: KeyEventKind::Press,
-                ..
-            }) => match code {
-                KeyCode::Char('q') => {
-                    terminal::disable_raw_mode().expect("Failed to disable raw mode");
-                    println!("Quitting...");
-                    std::process::exit(0);
-                }
-                KeyCode::Char('s') => {
-                    terminal::disable_raw_mode().expect("Failed to disable raw mode");
-                    refresh_display(GRID_SIZE);
-                    break;
-                }
-                KeyCode::Up => {
-                    if current_point.valid_up() {
-                        current_point.y = current_point.y - 1;
-                    } else {
-                        current_point.y = GRID_SIZE - 1;
-                    }
-                }
-                KeyCode::Down => {
-                    if current_point.valid_down() {
-                        current_point.y = current_point.y + 1;
-                    } else {
-                        current_point.y = 0;
-                    }
-                }
-                KeyCode::Left => {
-                    if current_point.valid_left() {
-                        current_point.x = current_point.x - 1;
-                    } else {
-                        current_point.x = GRID_SIZE - 1;
-                    }
+mod bits;
+mod cache;
+mod chasing;
+mod lit_only;
+mod modular;
+mod operator;
+mod search;
+
+use crate::graph::{Graph, GraphGame};
+use crate::{Game, NumberBoard, Point};
+use bits::{BitVec, WORD_BITS};
+use operator::SolutionOperator;
+use std::fmt;
+
+pub use cache::set_cache_directory;
+pub use chasing::{can_chase, solve_by_chasing};
+pub use lit_only::solve_lit_only;
+pub use search::solve_by_search;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SolveError {
+    /// Each violated check is a pattern of weights whose weighted sum of light states is not a multiple
+    /// of the number of states. Pressing any button changes that sum by a multiple of the number of states,
+    /// so it can never be brought to zero. With classic two-state lights the checks are quiet patterns
+    /// covering an odd number of lit cells. Checks for graph games have a single row, indexed by node.
+    Unsolvable {
+        violated_checks: Vec<NumberBoard>,
+        states: u8,
+    },
+    /// The board can be solved, but not when only lit lights may be pressed.
+    LitOnlyUnsolvable,
+    /// No presses bring the board to its target, found by searching every board they can reach.
+    Unreachable,
+    /// The board has too many lights to search every arrangement of them, as the lit-only solver and
+    /// `solve_by_search` do.
+    SearchTooLarge { lights: usize },
+}
+
+impl fmt::Display for SolveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SolveError::Unsolvable {
+                violated_checks,
+                states,
+            } => {
+                writeln!(f, "This board has no solution.")?;
+                if *states == 2 {
+                    writeln!(
+                        f,
+                        "A solvable board always has an even number of lights on in the cells marked '#' of each pattern below, but this board has an odd number:"
+                    )?;
+                } else {
+                    writeln!(
+                        f,
+                        "In a solvable board, multiplying each light's state by the number below it and adding them up always gives a multiple of {}, but this board does not:",
+                        states
+                    )?;
                 }
-                KeyCode::Right => {
-                    if current_point.valid_right() {
-                        current_point.x = current_point.x + 1;
-                    } else {
-                        current_point.x = 0;
+                for check in violated_checks {
+                    writeln!(f)?;
+                    for row in check {
+                        for &cell in row {
+                            match cell {
+                                0 => write!(f, " . ")?,
+                                1 if *states == 2 => write!(f, " # ")?,
+                                weight => write!(f, "{:^3}", weight)?,
+                            }
+                        }
+                        writeln!(f)?;
                     }
                 }
-                KeyCode::Enter => {
-                    terminal::disable_raw_mode().expect("Failed to disable raw mode");
-                    game.toggle_single_light(&current_point);
-                }
-                _ => {}
-            },
-            _ => {}
+                Ok(())
+            }
+            SolveError::LitOnlyUnsolvable => {
+                writeln!(
+                    f,
+                    "This board has no solution when only lit lights can be pressed."
+                )
+            }
+            SolveError::Unreachable => {
+                writeln!(
+                    f,
+                    "This board has no solution: no presses bring it to the target."
+                )
+            }
+            SolveError::SearchTooLarge { lights } => {
+                writeln!(
+                    f,
+                    "This board has {} lights, too many to search every arrangement of them (up to 25 classic lights).",
+                    lights
+                )
+            }
         }
-        terminal::disable_raw_mode().expect("Failed to disable raw mode");
-        refresh_display(GRID_SIZE);
-        game.display_with_selector(&current_point);
     }
-    refresh_display(1);
+}
+
+// Kernels up to this dimension are searched exhaustively (2^n candidate solutions) for the
+// solution with the fewest presses; larger kernels fall back to a greedy search.
+const MAX_EXHAUSTIVE_KERNEL_DIMENSION: usize = 20;
 
-    game
+/// Solves the Lights Out puzzle using Gaussian elimination and back substitution,
+/// then searches the null space of the toggle matrix for the solution with the fewest presses.
+/// Games with more than two states are solved with the same idea over the integers modulo the number of states.
+///
+/// The result holds the number of times to press each button, in any order.
+pub fn solve_lights_out(game: &Game) -> Result<NumberBoard, SolveError> {
+    let cells = game.cells();
+    let states = game.variant().states;
+
+    solve_system(&game.graph(), &linearize_puzzle(game, &cells), states)
+        .map(|solution| to_number_board(&solution, game, &cells))
+        .map_err(|violated_checks| SolveError::Unsolvable {
+            violated_checks: violated_checks
+                .iter()
+                .map(|check| to_number_board(check, game, &cells))
+                .collect(),
+            states,
+        })
 }
 
-// Solves the Lights Out puzzle using Gaussian elimination and back substitution.
-pub fn solve_lights_out(
-    board: &Board,
-    display: bool,
-) -> [[i32; GRID_SIZE as usize]; GRID_SIZE as usize] {
-    let toggle_matrix = make_toggle_matrix();
-    let puzzle_vector = linearize_puzzle(board);
-    perform_gaussian_elimination(toggle_matrix, puzzle_vector.clone());
-    let solution_vector = back_substitute(toggle_matrix, puzzle_vector);
-    let point_solution_vector = convert_solution_to_button_presses(solution_vector);
-
-    if display {
-        println!("Toggle the lights with numbers in any order");
-        display_point_solution_vector(point_solution_vector);
-    }
+/// Solves a graph game the same way, returning the number of times to press each node.
+pub fn solve_graph(game: &GraphGame) -> Result<Vec<i32>, SolveError> {
+    let lights: Vec<u8> = game.lights().iter().map(|light| light.state()).collect();
 
-    point_solution_vector
+    solve_system(game.graph(), &lights, game.states()).map_err(|violated_checks| {
+        SolveError::Unsolvable {
+            violated_checks: violated_checks
+                .into_iter()
+                .map(|check| vec![check])
+                .collect(),
+            states: game.states(),
+        }
+    })
 }
 
-// Creates a toggle matrix (25x25 for default game) for the given puzzle size, indicating the effect of pressing each button.
-// One row for each button
-fn make_toggle_matrix(
-) -> [[bool; GRID_SIZE as usize * GRID_SIZE as usize]; GRID_SIZE as usize * GRID_SIZE as usize] {
-    let mut matrix =
-        [[false; GRID_SIZE as usize * GRID_SIZE as usize]; GRID_SIZE as usize * GRID_SIZE as usize];
+// Solves for the presses that turn every light off, or returns the checks the lights fail.
+fn solve_system(graph: &Graph, lights: &[u8], states: u8) -> Result<Vec<i32>, Vec<Vec<i32>>> {
+    if states != 2 {
+        return modular::solve(graph, lights, states);
+    }
 
-    for y in 0..GRID_SIZE {
-        for x in 0..GRID_SIZE {
-            let index = (y * GRID_SIZE + x) as usize; // Flattening the grid to a single dimension
-            matrix[index][index] = true;
+    let operator = SolutionOperator::for_graph(graph);
+    let puzzle_vector = BitVec::from_bools(lights.iter().map(|&state| state == 1));
+    let to_numbers = |vector: &BitVec| -> Vec<i32> {
+        vector
+            .to_bools()
+            .into_iter()
+            .map(|value| value as i32)
+            .collect()
+    };
 
-            let point = Point::new(x as i32, y as i32);
+    operator
+        .solve(&puzzle_vector)
+        .map(|solution_vector| to_numbers(&solution_vector))
+        .map_err(|violated_checks| violated_checks.iter().map(to_numbers).collect())
+}
 
-            if point.valid_left() {
-                matrix[index][index - 1] = true;
-            }
-            if point.valid_right() {
-                matrix[index][index + 1] = true;
-            }
-            if point.valid_up() {
-                matrix[index][index - GRID_SIZE as usize] = true;
-            }
-            if point.valid_down() {
-                matrix[index][index + GRID_SIZE as usize] = true;
-            }
+// Creates a toggle matrix (25x25 for a 5x5 game) for the given graph, indicating the effect of pressing each button.
+// One row for each light and one column for each button, so entry (light, button) is set when the button toggles the light.
+//
+// Each row is followed by `extra` more columns, starting at the next word, for the caller to record row operations in.
+fn make_toggle_matrix(graph: &Graph, extra: usize) -> Vec<BitVec> {
+    let size = graph.node_count();
+    let mut matrix = vec![BitVec::new(size.next_multiple_of(WORD_BITS) + extra); size];
+
+    let toggles = (0..size).map(|button| graph.toggled_by(button));
+    for (button, lights) in toggles.enumerate() {
+        for &light in lights {
+            matrix[light].set(button, true);
         }
     }
 
     matrix
 }
 
-// Converts the puzzle matrix into a linear vector in row-major order.
-fn linearize_puzzle(board: &Board) -> Vec<bool> {
-    let mut linear_vector: Vec<bool> = Vec::new();
+// Converts the puzzle into a linear vector of light states, in the order of the board's cells (row-major, skipping holes).
+// Each light's distance from its target state, the steps the presses must take it through.
+// With every light's target off, that is just its state; with classic lights it is the light XOR its target.
+fn linearize_puzzle(game: &Game, cells: &[Point]) -> Vec<u8> {
+    let states = game.variant().states;
+    let mut linear_vector: Vec<u8> = Vec::new();
 
-    for row in board {
-        for square in row {
-            linear_vector.push(square.to_boolean());
-        }
+    for cell in cells {
+        let state = game.square(cell).state();
+        let target = game.target_square(cell).state();
+        linear_vector.push((state + states - target) % states);
     }
 
     linear_vector
 }
 
-fn perform_gaussian_elimination(
-    mut toggle_matrix: [[bool; GRID_SIZE as usize * GRID_SIZE as usize];
-        GRID_SIZE as usize * GRID_SIZE as usize],
-    mut puzzle_vector: Vec<bool>,
-) {
-    for col in 0..GRID_SIZE as usize * GRID_SIZE as usize {
-        let mut pivot_row = None;
-        for row in col..GRID_SIZE as usize * GRID_SIZE as usize {
-            if toggle_matrix[row][col] {
-                pivot_row = Some(row);
-                break;
-            }
-        }
+// Reduces the first `cols` columns of the matrix to row echelon form in place, carrying any columns after them along.
+// Returns the pivot column of each non-zero row, in row order.
+//
+// Rows are added a word at a time, and only rows below the pivot with its column set are touched. A board's
+// buttons only reach nearby lights, so the matrix is close to banded and stays that way, as long as rows above
+// the pivots are left alone; clearing them too (reduced row echelon form) fills the matrix in.
+fn perform_gaussian_elimination(matrix: &mut [BitVec], cols: usize) -> Vec<usize> {
+    let rows = matrix.len();
+    let mut pivots = Vec::new();
 
-        if let Some(pivot_row) = pivot_row {
-            toggle_matrix.swap(col, pivot_row);
-            puzzle_vector.swap(col, pivot_row);
+    for col in 0..cols {
+        let rank = pivots.len();
+        let Some(pivot_row) = (rank..rows).find(|&row| matrix[row].get(col)) else {
+            continue;
+        };
 
-            for row in col + 1..GRID_SIZE as usize * GRID_SIZE as usize {
-                if toggle_matrix[row][col] {
-                    for i in 0..GRID_SIZE as usize * GRID_SIZE as usize {
-                        toggle_matrix[row][i] ^= toggle_matrix[col][i];
-                    }
-                    puzzle_vector[row] ^= puzzle_vector[col];
-                }
+        matrix.swap(rank, pivot_row);
+
+        // The rows from the rank down are zero before this column, so only the words from it on need adding
+        let (pivot, below) = matrix[rank..].split_first_mut().expect("rank is a row");
+        for values in below {
+            if values.get(col) {
+                values.xor_from(pivot, col);
             }
         }
+
+        pivots.push(col);
     }
+
+    pivots
 }
 
-// Performs back substitution on a row-reduced toggle matrix to find a solution vector.
-fn back_substitute(
-    toggle_matrix: [[bool; GRID_SIZE as usize * GRID_SIZE as usize];
-        GRID_SIZE as usize * GRID_SIZE as usize],
-    puzzle_vector: Vec<bool>,
-) -> Vec<bool> {
-    let mut solution_vector = vec![false; GRID_SIZE as usize * GRID_SIZE as usize];
-
-    for row in (0..GRID_SIZE as usize * GRID_SIZE as usize).rev() {
-        if toggle_matrix[row].iter().all(|&x| x == false) && puzzle_vector[row] {
-            panic!("No solution");
-        }
+// Works out each pivot variable from the bottom row up, given the free variables already set in `vector`:
+// each row says its pivot variable plus the rest of the row times `vector` equals zero.
+fn substitute(matrix: &[BitVec], pivots: &[usize], mut vector: BitVec) -> BitVec {
+    for (row, &pivot) in pivots.iter().enumerate().rev() {
+        let value = matrix[row].odd_overlap(&vector);
+        vector.set(pivot, value);
+    }
 
-        let mut pivot = None;
-        for col in 0..GRID_SIZE as usize * GRID_SIZE as usize {
-            if toggle_matrix[row][col] {
-                pivot = Some(col);
-                break;
-            }
+    vector
+}
+
+// Builds a basis for the null space of the first `cols` columns of a matrix in row echelon form, one vector
+// per free column. For a toggle matrix, each basis vector is a set of presses that leaves the board unchanged
+// (a "quiet pattern").
+fn find_kernel_basis(matrix: &[BitVec], pivots: &[usize], cols: usize) -> Vec<BitVec> {
+    let mut is_pivot = vec![false; cols];
+    for &pivot in pivots {
+        is_pivot[pivot] = true;
+    }
+
+    (0..cols)
+        .filter(|&col| !is_pivot[col])
+        .map(|free| {
+            let mut vector = BitVec::new(cols);
+            vector.set(free, true);
+            substitute(matrix, pivots, vector)
+        })
+        .collect()
+}
+
+// A board is solvable exactly when it covers an even number of lit cells under every vector
+// in the null space of the transposed toggle matrix. Returns a basis of those vectors.
+fn find_checks(graph: &Graph) -> Vec<BitVec> {
+    let size = graph.node_count();
+    let mut transposed = vec![BitVec::new(size); size];
+    for (button, row) in transposed.iter_mut().enumerate() {
+        for &light in graph.toggled_by(button) {
+            row.set(light, true);
         }
+    }
+    let pivots = perform_gaussian_elimination(&mut transposed, size);
 
-        if let Some(pivot) = pivot {
-            let mut value = puzzle_vector[row];
-            for col in pivot + 1..GRID_SIZE as usize * GRID_SIZE as usize {
-                if toggle_matrix[row][col] {
-                    value ^= solution_vector[col];
-                }
-            }
-            solution_vector[pivot] = value;
+    find_kernel_basis(&transposed, &pivots, size)
+}
+
+// Every solution is the particular solution XOR some combination of kernel vectors,
+// so this searches those combinations for the one with the fewest presses.
+fn find_minimum_solution(solution_vector: BitVec, kernel_basis: &[BitVec]) -> BitVec {
+    if kernel_basis.len() > MAX_EXHAUSTIVE_KERNEL_DIMENSION {
+        return improve_solution_greedily(solution_vector, kernel_basis);
+    }
+
+    let mut current = solution_vector.clone();
+    let mut best_presses = current.count_ones();
+    let mut best = solution_vector;
+
+    // Walks every combination in Gray code order, so each step only adds a single kernel vector
+    for step in 1..1usize << kernel_basis.len() {
+        current.xor(&kernel_basis[step.trailing_zeros() as usize]);
+        let presses = current.count_ones();
+        if presses < best_presses {
+            best.clone_from(&current);
+            best_presses = presses;
         }
     }
 
-    solution_vector
+    best
 }
 
-// Converts a solution vector back into a list of (row, col) pairs indicating button presses.
-fn convert_solution_to_button_presses(
-    solution_vector: Vec<bool>,
-) -> [[i32; GRID_SIZE as usize]; GRID_SIZE as usize] {
-    let mut point_solution_vector: [[i32; GRID_SIZE as usize]; GRID_SIZE as usize] =
-        [[0; GRID_SIZE as usize]; GRID_SIZE as usize];
-
-    for y in 0..GRID_SIZE {
-        for x in 0..GRID_SIZE {
-            let index = (y * GRID_SIZE + x) as usize;
-            if solution_vector[index] {
-                point_solution_vector[y as usize][x as usize] += 1;
+// Repeatedly applies any single kernel vector that lowers the press count.
+// This is not guaranteed to be optimal, but keeps very large kernels tractable.
+fn improve_solution_greedily(mut solution_vector: BitVec, kernel_basis: &[BitVec]) -> BitVec {
+    let mut presses = solution_vector.count_ones();
+    let mut improved = true;
+
+    while improved {
+        improved = false;
+        for vector in kernel_basis {
+            let mut candidate = solution_vector.clone();
+            candidate.xor(vector);
+            let candidate_presses = candidate.count_ones();
+            if candidate_presses < presses {
+                solution_vector = candidate;
+                presses = candidate_presses;
+                improved = true;
             }
         }
     }
 
-    point_solution_vector
+    solution_vector
 }
 
-fn display_point_solution_vector(
-    point_solution_vector: [[i32; GRID_SIZE as usize]; GRID_SIZE as usize],
-) {
-    for column in point_solution_vector {
-        for point in column {
-            print!(" {} ", point);
-        }
-        println!();
+// Places a value for each of the board's cells back on the board, leaving holes at 0.
+fn to_number_board(vector: &[i32], game: &Game, cells: &[Point]) -> NumberBoard {
+    let mut board = vec![vec![0; game.width() as usize]; game.height() as usize];
+    for (cell, &value) in cells.iter().zip(vector) {
+        board[cell.y as usize][cell.x as usize] = value;
     }
+
+    board
 }
@@ -0,0 +1,147 @@
+// A fixed-length vector of bits over GF(2), packed 64 to a word so that adding two vectors
+// (XOR) and counting set bits work a whole word at a time.
+
+pub(super) const WORD_BITS: usize = u64::BITS as usize;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct BitVec {
+    words: Vec<u64>,
+    len: usize,
+}
+
+impl BitVec {
+    // A vector of `len` zero bits.
+    pub(super) fn new(len: usize) -> Self {
+        BitVec {
+            words: vec![0; len.div_ceil(WORD_BITS)],
+            len,
+        }
+    }
+
+    pub(super) fn from_bools(bits: impl IntoIterator<Item = bool>) -> Self {
+        let bits: Vec<bool> = bits.into_iter().collect();
+        let mut vector = BitVec::new(bits.len());
+        for (index, bit) in bits.into_iter().enumerate() {
+            vector.set(index, bit);
+        }
+
+        vector
+    }
+
+    // A vector of `len` bits stored in `words`, lowest bit first, as returned by `words`.
+    pub(super) fn from_words(words: Vec<u64>, len: usize) -> Option<Self> {
+        (words.len() == len.div_ceil(WORD_BITS)).then_some(BitVec { words, len })
+    }
+
+    pub(super) fn words(&self) -> &[u64] {
+        &self.words
+    }
+
+    // The `len` bits starting at `start`, which must be at the start of a word.
+    pub(super) fn slice(&self, start: usize, len: usize) -> BitVec {
+        assert!(
+            start.is_multiple_of(WORD_BITS),
+            "slices start at a word boundary"
+        );
+        let first = start / WORD_BITS;
+        let mut words = self.words[first..first + len.div_ceil(WORD_BITS)].to_vec();
+        if let Some(last) = words.last_mut().filter(|_| !len.is_multiple_of(WORD_BITS)) {
+            *last &= (1 << (len % WORD_BITS)) - 1;
+        }
+
+        BitVec { words, len }
+    }
+
+    // The index of every set bit, in increasing order.
+    pub(super) fn ones(&self) -> impl Iterator<Item = usize> + '_ {
+        self.words.iter().enumerate().flat_map(|(index, &word)| {
+            let mut word = word;
+            std::iter::from_fn(move || {
+                if word == 0 {
+                    return None;
+                }
+                let bit = word.trailing_zeros() as usize;
+                word &= word - 1;
+                Some(index * WORD_BITS + bit)
+            })
+        })
+    }
+
+    // This vector with every bit moved to the next index (up) or the previous one (down), dropping the bit
+    // that falls off the end; the neighbours of each bit in a row of lights.
+    pub(super) fn shifted(&self, up: bool) -> BitVec {
+        let count = self.words.len();
+        let mut words: Vec<u64> = (0..count)
+            .map(|index| {
+                let word = self.words[index];
+                if up {
+                    let carry = index
+                        .checked_sub(1)
+                        .map_or(0, |below| self.words[below] >> 63);
+                    word << 1 | carry
+                } else {
+                    let carry = self.words.get(index + 1).map_or(0, |above| above << 63);
+                    word >> 1 | carry
+                }
+            })
+            .collect();
+        if let Some(last) = words
+            .last_mut()
+            .filter(|_| !self.len.is_multiple_of(WORD_BITS))
+        {
+            *last &= (1 << (self.len % WORD_BITS)) - 1;
+        }
+
+        BitVec {
+            words,
+            len: self.len,
+        }
+    }
+
+    pub(super) fn get(&self, index: usize) -> bool {
+        self.words[index / WORD_BITS] >> (index % WORD_BITS) & 1 == 1
+    }
+
+    pub(super) fn set(&mut self, index: usize, bit: bool) {
+        let mask = 1 << (index % WORD_BITS);
+        if bit {
+            self.words[index / WORD_BITS] |= mask;
+        } else {
+            self.words[index / WORD_BITS] &= !mask;
+        }
+    }
+
+    // Adds `other` to this vector. Bits before `from` must be zero in `other`; they are skipped a word at a time.
+    pub(super) fn xor_from(&mut self, other: &BitVec, from: usize) {
+        let start = from / WORD_BITS;
+        for (word, other) in self.words[start..].iter_mut().zip(&other.words[start..]) {
+            *word ^= other;
+        }
+    }
+
+    pub(super) fn xor(&mut self, other: &BitVec) {
+        self.xor_from(other, 0);
+    }
+
+    pub(super) fn count_ones(&self) -> usize {
+        self.words
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    // Whether this vector and `other` share an odd number of set bits, their dot product over GF(2).
+    pub(super) fn odd_overlap(&self, other: &BitVec) -> bool {
+        self.words
+            .iter()
+            .zip(&other.words)
+            .map(|(word, other)| (word & other).count_ones())
+            .sum::<u32>()
+            % 2
+            == 1
+    }
+
+    pub(super) fn to_bools(&self) -> Vec<bool> {
+        (0..self.len).map(|index| self.get(index)).collect()
+    }
+}
@@ -0,0 +1,88 @@
+// Solver tables are worked out once per graph and kept in memory, so repeated solves of boards with the same
+// size, shape and rules (hints, difficulty filtering, batch solving) skip the elimination. Tables for classic
+// boards can also be kept in a directory, so they last between runs.
+use crate::graph::Graph;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+// Tables for large boards take megabytes, so only this many are kept in memory by default; a cache is emptied when
+// it fills up.
+const MAX_ENTRIES: usize = 16;
+
+static DIRECTORY: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+/// Keeps the solver's tables for classic boards in `directory` between runs, or only in memory when `None` (the default).
+/// Tables are written the first time a board of each size, shape and rule set is solved, and read back after that.
+pub fn set_cache_directory(directory: Option<PathBuf>) {
+    *DIRECTORY.lock().unwrap_or_else(PoisonError::into_inner) = directory;
+}
+
+// The file in the cache directory for the graph's tables, if a directory is set.
+pub(super) fn file_for(graph: &Graph) -> Option<PathBuf> {
+    let directory = DIRECTORY.lock().unwrap_or_else(PoisonError::into_inner);
+
+    directory
+        .as_ref()
+        .map(|directory| directory.join(format!("{:016x}.lights-out", fingerprint(graph))))
+}
+
+// FNV-1a over the graph's toggles. Unlike the standard library's hasher, it stays the same between builds,
+// so file names from earlier runs still match.
+fn fingerprint(graph: &Graph) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    let mut add = |value: usize| {
+        for byte in (value as u64).to_le_bytes() {
+            hash = (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    };
+    add(graph.node_count());
+    for node in 0..graph.node_count() {
+        add(graph.toggled_by(node).len());
+        graph.toggled_by(node).iter().for_each(|&other| add(other));
+    }
+
+    hash
+}
+
+pub(super) struct Cache<K, T> {
+    entries: Mutex<Option<HashMap<K, Arc<T>>>>,
+    capacity: usize,
+}
+
+impl<K: Hash + Eq + Clone, T> Cache<K, T> {
+    pub(super) const fn new() -> Self {
+        Cache::with_capacity(MAX_ENTRIES)
+    }
+
+    // A cache that keeps at most `capacity` tables, for tables much larger than usual.
+    pub(super) const fn with_capacity(capacity: usize) -> Self {
+        Cache {
+            entries: Mutex::new(None),
+            capacity,
+        }
+    }
+
+    // The entry for `key`, building and remembering it first if there is none yet.
+    pub(super) fn get_or_insert_with(&self, key: &K, build: impl FnOnce() -> T) -> Arc<T> {
+        if let Some(entry) = self.lock().as_ref().and_then(|entries| entries.get(key)) {
+            return Arc::clone(entry);
+        }
+
+        // Built without holding the lock, so other threads' solves are not held up by a large board
+        let entry = Arc::new(build());
+        let mut entries = self.lock();
+        let entries = entries.get_or_insert_with(HashMap::new);
+        if entries.len() >= self.capacity {
+            entries.clear();
+        }
+        entries.insert(key.clone(), Arc::clone(&entry));
+
+        entry
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Option<HashMap<K, Arc<T>>>> {
+        self.entries.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
@@ -0,0 +1,158 @@
+// Solves classic boards by light chasing. Once the top row's presses are chosen, a lit light can only be turned
+// off by the button below it, as every other button that reaches it has been settled, so each row's presses
+// follow from the rows above. Only the lights left in the bottom row depend on the top row, and linearly, so
+// the top row comes from a system with one equation per column instead of one per light.
+//
+// Chasing a row costs a few word operations, so setting up the system takes width chases of the whole board
+// and solving it is elimination on a width x width matrix: far less than eliminating the full toggle matrix.
+use super::bits::BitVec;
+use super::{
+    find_kernel_basis, find_minimum_solution, linearize_puzzle, perform_gaussian_elimination,
+    solve_lights_out, substitute, SolveError,
+};
+use crate::{Game, Grid, NumberBoard, Topology};
+
+/// Solves the board by light chasing, finding the same minimum number of presses as `solve_lights_out` in far
+/// less time and memory on large boards, such as 200x200.
+///
+/// Chasing works on boards without holes or wrapping edges, with two-state lights, where a press toggles the
+/// orthogonal neighbours and, unless playing the sigma game, the pressed light. Other boards are solved with
+/// `solve_lights_out`.
+///
+/// The checks an unsolvable board fails are quiet patterns, presses that leave the board unchanged: these
+/// boards' presses are symmetric, so a light's checks are the same as its quiet patterns.
+pub fn solve_by_chasing(game: &Game) -> Result<NumberBoard, SolveError> {
+    let Some(presses_self) = chasing_rule(game) else {
+        return solve_lights_out(game);
+    };
+
+    let width = game.width() as usize;
+    let height = game.height() as usize;
+    let chase = Chase {
+        height,
+        presses_self,
+    };
+    let lights: Vec<BitVec> = linearize_puzzle(game, &game.cells())
+        .chunks(width)
+        .map(|row| BitVec::from_bools(row.iter().map(|&state| state == 1)))
+        .collect();
+    let dark = vec![BitVec::new(width); height];
+
+    // The lights left in the bottom row are M * top + c: c from chasing the lights without top row presses,
+    // and each column of M from chasing a single top row press on a dark board
+    let (_, leftover) = chase.presses(BitVec::new(width), &lights);
+    let columns: Vec<BitVec> = (0..width)
+        .map(|column| {
+            let mut top = BitVec::new(width);
+            top.set(column, true);
+            chase.presses(top, &dark).1
+        })
+        .collect();
+
+    // One row per bottom row light: M, then c in the last column
+    let mut matrix: Vec<BitVec> = (0..width)
+        .map(|x| {
+            BitVec::from_bools(
+                columns
+                    .iter()
+                    .map(|column| column.get(x))
+                    .chain([leftover.get(x)]),
+            )
+        })
+        .collect();
+    let pivots = perform_gaussian_elimination(&mut matrix, width);
+
+    // Top rows that leave a dark bottom row on a dark board chase down into the quiet patterns
+    let quiet_patterns: Vec<BitVec> = find_kernel_basis(&matrix, &pivots, width)
+        .into_iter()
+        .map(|top| flatten(&chase.presses(top, &dark).0))
+        .collect();
+
+    if (pivots.len()..width).any(|row| matrix[row].get(width)) {
+        let lit = flatten(&lights);
+        return Err(SolveError::Unsolvable {
+            violated_checks: quiet_patterns
+                .iter()
+                .filter(|pattern| pattern.odd_overlap(&lit))
+                .map(|pattern| to_number_board(pattern, width))
+                .collect(),
+            states: 2,
+        });
+    }
+
+    // Solving [M | c] with the last column's variable fixed to 1 gives a top row with M * top = c
+    let mut top = BitVec::new(width + 1);
+    top.set(width, true);
+    let top = substitute(&matrix, &pivots, top).slice(0, width);
+    let solution_vector = flatten(&chase.presses(top, &lights).0);
+    let solution_vector = find_minimum_solution(solution_vector, &quiet_patterns);
+
+    Ok(to_number_board(&solution_vector, width))
+}
+
+/// Whether `solve_by_chasing` solves the board by light chasing: a board without holes or wrapping edges, with
+/// two-state lights, where a press toggles the orthogonal neighbours and possibly the pressed light.
+pub fn can_chase(game: &Game) -> bool {
+    chasing_rule(game).is_some()
+}
+
+// Whether light chasing works for the game and, if it does, whether a press toggles the pressed light.
+fn chasing_rule(game: &Game) -> Option<bool> {
+    let variant = game.variant();
+    let mut offsets = variant.neighbourhood();
+    offsets.sort_unstable();
+    offsets.dedup();
+    let presses_self = offsets.contains(&(0, 0));
+    offsets.retain(|&offset| offset != (0, 0));
+
+    let applies = variant.grid == Grid::Square
+        && variant.topology == Topology::Bounded
+        && variant.states == 2
+        && offsets == [(-1, 0), (0, -1), (0, 1), (1, 0)]
+        && game.cells().len() == (game.width() * game.height()) as usize;
+    applies.then_some(presses_self)
+}
+
+struct Chase {
+    height: usize,
+    presses_self: bool,
+}
+
+impl Chase {
+    // The presses in each row, starting from `top`, that turn off every light above the bottom row, and the
+    // lights they leave on in the bottom row.
+    fn presses(&self, top: BitVec, lights: &[BitVec]) -> (Vec<BitVec>, BitVec) {
+        let mut rows = vec![top];
+        for y in 0..self.height {
+            // The light above each button in the next row, after the presses in its own row and the row above
+            let row = &rows[y];
+            let mut next = lights[y].clone();
+            if self.presses_self {
+                next.xor(row);
+            }
+            next.xor(&row.shifted(true));
+            next.xor(&row.shifted(false));
+            if let Some(above) = y.checked_sub(1) {
+                next.xor(&rows[above]);
+            }
+            rows.push(next);
+        }
+
+        let leftover = rows
+            .pop()
+            .expect("there is a row per board row and one more");
+        (rows, leftover)
+    }
+}
+
+fn flatten(rows: &[BitVec]) -> BitVec {
+    BitVec::from_bools(rows.iter().flat_map(BitVec::to_bools))
+}
+
+fn to_number_board(vector: &BitVec, width: usize) -> NumberBoard {
+    vector
+        .to_bools()
+        .chunks(width)
+        .map(|row| row.iter().map(|&value| value as i32).collect())
+        .collect()
+}
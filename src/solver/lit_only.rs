@@ -0,0 +1,48 @@
+// Solves the lit-only variant, where only lights that are on may be pressed, with a breadth-first search over boards.
+// The order of presses matters under this rule, so the linear algebra in the parent module cannot be used.
+use super::search::{self, Search, START};
+use super::SolveError;
+use crate::{Game, Point};
+use std::collections::VecDeque;
+
+/// Finds the shortest sequence of presses that brings the board to its target when only lit lights may be pressed.
+/// Boards with more than 2^25 possible arrangements of lights are too large to search.
+///
+/// The presses are returned in the order they must be made.
+pub fn solve_lit_only(game: &Game) -> Result<Vec<Point>, SolveError> {
+    // Any lit-only solution is also a solution without the restriction, so boards
+    // that have none are ruled out quickly, and with an explanation
+    super::solve_lights_out(game)?;
+
+    let cells = game.cells();
+    let states = game.variant().states as u64;
+    let boards = search::board_count(cells.len(), states)?;
+    let search = Search::new(game.graph(), states);
+    let start = search.number(game, &cells, Game::square);
+    let goal = search.number(game, &cells, Game::target_square);
+
+    // The button pressed to first reach each board, plus one; 0 for boards not reached yet
+    let mut reached_by = vec![0u8; boards as usize];
+    reached_by[start as usize] = START;
+    let mut queue = VecDeque::from([start]);
+
+    while let Some(board) = queue.pop_front() {
+        if board == goal {
+            return Ok(search
+                .presses_to(board, &reached_by)
+                .into_iter()
+                .map(|button| cells[button])
+                .collect());
+        }
+
+        for button in (0..cells.len()).filter(|&button| search.digit(board, button) != 0) {
+            let next = search.press(board, button);
+            if reached_by[next as usize] == 0 {
+                reached_by[next as usize] = button as u8 + 1;
+                queue.push_back(next);
+            }
+        }
+    }
+
+    Err(SolveError::LitOnlyUnsolvable)
+}
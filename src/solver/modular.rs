@@ -0,0 +1,359 @@
+// Solves variants whose lights cycle through k > 2 states, working with integers modulo k.
+//
+// Z/kZ is not a field when k is not prime (some non-zero values have no inverse), so plain Gaussian
+// elimination does not work. Instead the toggle matrix A is diagonalised with row and column operations
+// of determinant 1, which stay invertible modulo any k (as in the Smith normal form):
+//
+//     U * A * V = D
+//
+// Then A x = b becomes D y = U b, which is one independent equation per diagonal entry, and x = V y.
+use super::cache::Cache;
+use crate::graph::Graph;
+
+// Solution spaces with up to this many solutions are searched exhaustively for the one with the fewest
+// presses; larger ones fall back to a greedy search.
+const MAX_ENUMERATED_SOLUTIONS: u64 = 1 << 16;
+
+// The diagonalisation only depends on the graph and the number of states, so it is worked out once for each pair
+static DIAGONALISATIONS: Cache<(Graph, u8), Diagonalisation> = Cache::new();
+
+struct Diagonalisation {
+    // The non-zero diagonal entries of D, one per pivot; every other entry of D is zero
+    diagonal: Vec<i64>,
+    row_transform: Vec<Vec<i64>>,
+    column_transform: Vec<Vec<i64>>,
+}
+
+// Returns the number of times to press each button, or the checks the lights fail.
+pub fn solve(graph: &Graph, lights: &[u8], states: u8) -> Result<Vec<i32>, Vec<Vec<i32>>> {
+    let modulus = states as i64;
+    let buttons = graph.node_count();
+    let puzzle_vector: Vec<i64> = lights.iter().map(|&state| state as i64).collect();
+
+    // Presses add to the lights' states, so the presses must add up to minus each light's state to wrap it back to 0
+    let target: Vec<i64> = puzzle_vector
+        .iter()
+        .map(|state| (-state).rem_euclid(modulus))
+        .collect();
+
+    let diagonalisation = DIAGONALISATIONS.get_or_insert_with(&(graph.clone(), states), || {
+        diagonalise(make_toggle_matrix(graph), modulus)
+    });
+    let transformed = multiply(&diagonalisation.row_transform, &target, modulus);
+
+    let violated_checks = find_violated_checks(&diagonalisation, &puzzle_vector, modulus);
+    if !violated_checks.is_empty() {
+        return Err(violated_checks
+            .iter()
+            .map(|check| to_numbers(check))
+            .collect());
+    }
+
+    // Each equation d * y = c has gcd(d, k) solutions modulo k, spaced k / gcd(d, k) apart.
+    // The free columns past the last pivot can take any value.
+    let mut reduced = vec![0; buttons];
+    let mut generators = Vec::new();
+    for (index, &entry) in diagonalisation.diagonal.iter().enumerate() {
+        let divisor = gcd(entry, modulus);
+        let step = modulus / divisor;
+        reduced[index] = (transformed[index] / divisor) * inverse(entry / divisor, step) % step;
+        if divisor > 1 {
+            generators.push((
+                column(&diagonalisation.column_transform, index, step, modulus),
+                divisor,
+            ));
+        }
+    }
+    for index in diagonalisation.diagonal.len()..buttons {
+        generators.push((
+            column(&diagonalisation.column_transform, index, 1, modulus),
+            modulus,
+        ));
+    }
+
+    let solution_vector = multiply(&diagonalisation.column_transform, &reduced, modulus);
+    let solution_vector = find_minimum_solution(solution_vector, &generators, modulus);
+
+    Ok(to_numbers(&solution_vector))
+}
+
+// Diagonalises the matrix with unimodular row and column operations, tracking them in U and V.
+fn diagonalise(mut matrix: Vec<Vec<i64>>, modulus: i64) -> Diagonalisation {
+    let rows = matrix.len();
+    let cols = matrix.first().map_or(0, Vec::len);
+    let mut row_transform = identity(rows);
+    let mut column_transform = identity(cols);
+    let mut diagonal = Vec::new();
+
+    for pivot in 0..rows.min(cols) {
+        // The smallest remaining entry makes a good pivot, as it divides the most other entries
+        let mut best: Option<(usize, usize)> = None;
+        for (row, values) in matrix.iter().enumerate().skip(pivot) {
+            for (col, &value) in values.iter().enumerate().skip(pivot) {
+                if value != 0 && best.is_none_or(|(r, c)| value < matrix[r][c]) {
+                    best = Some((row, col));
+                }
+            }
+        }
+        let Some((row, col)) = best else {
+            break;
+        };
+
+        matrix.swap(pivot, row);
+        row_transform.swap(pivot, row);
+        for values in matrix.iter_mut().chain(column_transform.iter_mut()) {
+            values.swap(pivot, col);
+        }
+
+        // Column operations can put entries back below the pivot, so repeat until the pivot's row
+        // and column are both clear. The pivot only ever shrinks to a divisor of itself, so this ends.
+        loop {
+            for row in pivot + 1..rows {
+                if matrix[row][pivot] != 0 {
+                    let coefficients = eliminate(matrix[pivot][pivot], matrix[row][pivot]);
+                    combine_rows(&mut matrix, pivot, row, coefficients, modulus);
+                    combine_rows(&mut row_transform, pivot, row, coefficients, modulus);
+                }
+            }
+            for col in pivot + 1..cols {
+                if matrix[pivot][col] != 0 {
+                    let coefficients = eliminate(matrix[pivot][pivot], matrix[pivot][col]);
+                    combine_columns(&mut matrix, pivot, col, coefficients, modulus);
+                    combine_columns(&mut column_transform, pivot, col, coefficients, modulus);
+                }
+            }
+            if (pivot + 1..rows).all(|row| matrix[row][pivot] == 0) {
+                break;
+            }
+        }
+
+        diagonal.push(matrix[pivot][pivot]);
+    }
+
+    Diagonalisation {
+        diagonal,
+        row_transform,
+        column_transform,
+    }
+}
+
+// One row for each light and one column for each button, with a 1 where the button advances the light.
+fn make_toggle_matrix(graph: &Graph) -> Vec<Vec<i64>> {
+    let size = graph.node_count();
+    let mut matrix = vec![vec![0; size]; size];
+    let toggles = (0..size).map(|button| graph.toggled_by(button));
+    for (button, lights) in toggles.enumerate() {
+        for &light in lights {
+            matrix[light][button] = 1;
+        }
+    }
+
+    matrix
+}
+
+// Returns the 2x2 operation [[a, b], [c, d]] with determinant 1 that turns (pivot, other)
+// into (gcd, 0). When the pivot already divides the other entry, the pivot is left untouched.
+fn eliminate(pivot: i64, other: i64) -> [[i64; 2]; 2] {
+    if other % pivot == 0 {
+        return [[1, 0], [-(other / pivot), 1]];
+    }
+
+    let (divisor, a, b) = extended_gcd(pivot, other);
+    [[a, b], [-(other / divisor), pivot / divisor]]
+}
+
+// Replaces rows (first, second) with (a * first + b * second, c * first + d * second).
+fn combine_rows(
+    matrix: &mut [Vec<i64>],
+    first: usize,
+    second: usize,
+    [[a, b], [c, d]]: [[i64; 2]; 2],
+    modulus: i64,
+) {
+    for col in 0..matrix[first].len() {
+        let (x, y) = (matrix[first][col], matrix[second][col]);
+        matrix[first][col] = (a * x + b * y).rem_euclid(modulus);
+        matrix[second][col] = (c * x + d * y).rem_euclid(modulus);
+    }
+}
+
+// Replaces columns (first, second) with (a * first + b * second, c * first + d * second).
+fn combine_columns(
+    matrix: &mut [Vec<i64>],
+    first: usize,
+    second: usize,
+    [[a, b], [c, d]]: [[i64; 2]; 2],
+    modulus: i64,
+) {
+    for row in matrix.iter_mut() {
+        let (x, y) = (row[first], row[second]);
+        row[first] = (a * x + b * y).rem_euclid(modulus);
+        row[second] = (c * x + d * y).rem_euclid(modulus);
+    }
+}
+
+// The weighted sum of light states under each check must be a multiple of k for the board to be solvable:
+// rows of U past the last pivot must give 0, and the others a multiple of gcd(d, k).
+fn find_violated_checks(
+    diagonalisation: &Diagonalisation,
+    puzzle_vector: &[i64],
+    modulus: i64,
+) -> Vec<Vec<i64>> {
+    let mut checks = Vec::new();
+    for (index, row) in diagonalisation.row_transform.iter().enumerate() {
+        let scale = match diagonalisation.diagonal.get(index) {
+            Some(&entry) => modulus / gcd(entry, modulus),
+            None => 1,
+        };
+        if scale == modulus {
+            continue;
+        }
+
+        let check: Vec<i64> = row.iter().map(|value| value * scale % modulus).collect();
+        let sum: i64 = check
+            .iter()
+            .zip(puzzle_vector)
+            .map(|(weight, state)| weight * state)
+            .sum();
+        if sum % modulus != 0 {
+            checks.push(check);
+        }
+    }
+
+    checks
+}
+
+// Every solution is the particular solution plus some multiple of each generator, where a generator
+// used `count` times wraps back to nothing. Searches those combinations for the fewest presses.
+fn find_minimum_solution(
+    solution_vector: Vec<i64>,
+    generators: &[(Vec<i64>, i64)],
+    modulus: i64,
+) -> Vec<i64> {
+    let combinations = generators
+        .iter()
+        .try_fold(1u64, |total, &(_, count)| total.checked_mul(count as u64))
+        .filter(|&total| total <= MAX_ENUMERATED_SOLUTIONS);
+    let Some(combinations) = combinations else {
+        return improve_solution_greedily(solution_vector, generators, modulus);
+    };
+
+    let mut digits = vec![0; generators.len()];
+    let mut current = solution_vector.clone();
+    let mut best = solution_vector;
+    let mut best_presses = count_presses(&best);
+
+    // Counts through every combination like an odometer, adding one generator per step
+    for _ in 1..combinations {
+        for (digit, (generator, count)) in digits.iter_mut().zip(generators) {
+            add(&mut current, generator, 1, modulus);
+            *digit += 1;
+            if *digit < *count {
+                break;
+            }
+            *digit = 0;
+        }
+
+        let presses = count_presses(&current);
+        if presses < best_presses {
+            best.clone_from(&current);
+            best_presses = presses;
+        }
+    }
+
+    best
+}
+
+// Repeatedly applies any generator multiple that lowers the press count.
+// This is not guaranteed to be optimal, but keeps very large solution spaces tractable.
+fn improve_solution_greedily(
+    mut solution_vector: Vec<i64>,
+    generators: &[(Vec<i64>, i64)],
+    modulus: i64,
+) -> Vec<i64> {
+    let mut presses = count_presses(&solution_vector);
+    let mut improved = true;
+
+    while improved {
+        improved = false;
+        for (generator, count) in generators {
+            for times in 1..*count {
+                let mut candidate = solution_vector.clone();
+                add(&mut candidate, generator, times, modulus);
+                let candidate_presses = count_presses(&candidate);
+                if candidate_presses < presses {
+                    solution_vector = candidate;
+                    presses = candidate_presses;
+                    improved = true;
+                }
+            }
+        }
+    }
+
+    solution_vector
+}
+
+fn count_presses(solution_vector: &[i64]) -> i64 {
+    solution_vector.iter().sum()
+}
+
+fn add(vector: &mut [i64], other: &[i64], times: i64, modulus: i64) {
+    for (value, other) in vector.iter_mut().zip(other) {
+        *value = (*value + times * other).rem_euclid(modulus);
+    }
+}
+
+fn multiply(matrix: &[Vec<i64>], vector: &[i64], modulus: i64) -> Vec<i64> {
+    matrix
+        .iter()
+        .map(|row| {
+            row.iter()
+                .zip(vector)
+                .map(|(a, b)| a * b % modulus)
+                .sum::<i64>()
+                % modulus
+        })
+        .collect()
+}
+
+// Column `index` of the matrix, scaled by `scale`.
+fn column(matrix: &[Vec<i64>], index: usize, scale: i64, modulus: i64) -> Vec<i64> {
+    matrix
+        .iter()
+        .map(|row| row[index] * scale % modulus)
+        .collect()
+}
+
+fn identity(size: usize) -> Vec<Vec<i64>> {
+    (0..size)
+        .map(|row| (0..size).map(|col| i64::from(row == col)).collect())
+        .collect()
+}
+
+fn to_numbers(vector: &[i64]) -> Vec<i32> {
+    vector.iter().map(|&value| value as i32).collect()
+}
+
+fn gcd(a: i64, b: i64) -> i64 {
+    if b == 0 {
+        a.abs()
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+// Returns (g, a, b) with a * x + b * y = g = gcd(x, y).
+fn extended_gcd(x: i64, y: i64) -> (i64, i64, i64) {
+    if y == 0 {
+        (x, 1, 0)
+    } else {
+        let (divisor, a, b) = extended_gcd(y, x % y);
+        (divisor, b, a - (x / y) * b)
+    }
+}
+
+// The inverse of `value` modulo `modulus`, which must be coprime to it.
+fn inverse(value: i64, modulus: i64) -> i64 {
+    let (_, a, _) = extended_gcd(value.rem_euclid(modulus), modulus);
+    a.rem_euclid(modulus)
+}
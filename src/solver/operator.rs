@@ -0,0 +1,178 @@
+// The solution operator for classic two-state lights on one graph: everything about solving that does not depend
+// on the lights, worked out once so that each solve is a matrix-vector product.
+//
+// Eliminating the toggle matrix A alongside the identity gives E * A = R, with R in row echelon form and E recording
+// the row operations. Back substitution through R is linear in E * b, so running it on the rows of E instead of on
+// a single board gives a matrix P where x = P * b solves A * x = b for every solvable board b.
+use super::bits::{BitVec, WORD_BITS};
+use super::cache::{self, Cache};
+use super::{
+    find_checks, find_kernel_basis, find_minimum_solution, make_toggle_matrix,
+    perform_gaussian_elimination,
+};
+use crate::graph::Graph;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+
+static OPERATORS: Cache<Graph, SolutionOperator> = Cache::new();
+
+// Changed whenever the file layout in `save` changes, so older files are rebuilt rather than misread
+const FILE_VERSION: u64 = 1;
+
+pub(super) struct SolutionOperator {
+    // A row per button: the lights whose parity gives that button's press in a particular solution, the rows of P
+    inverse: Vec<BitVec>,
+    // Solvable boards have an even number of lit lights under each check, as found by `find_checks`
+    checks: Vec<BitVec>,
+    kernel_basis: Vec<BitVec>,
+}
+
+impl SolutionOperator {
+    // The graph's operator from memory or the cache directory, or worked out now and remembered in both.
+    pub(super) fn for_graph(graph: &Graph) -> Arc<SolutionOperator> {
+        OPERATORS.get_or_insert_with(graph, || {
+            let path = cache::file_for(graph);
+            if let Some(operator) = path
+                .as_ref()
+                .and_then(|path| SolutionOperator::load(graph, path))
+            {
+                return operator;
+            }
+
+            let operator = SolutionOperator::new(graph);
+            if let Some(path) = path {
+                // The directory only saves time on later runs, so a board still solves when it cannot be written
+                let _ = operator.save(graph, &path);
+            }
+            operator
+        })
+    }
+
+    fn new(graph: &Graph) -> Self {
+        let size = graph.node_count();
+        let offset = size.next_multiple_of(WORD_BITS);
+        let mut matrix = make_toggle_matrix(graph, size);
+        for (light, row) in matrix.iter_mut().enumerate() {
+            row.set(offset + light, true);
+        }
+        let pivots = perform_gaussian_elimination(&mut matrix, size);
+
+        // Back substitution from the bottom row up, as in `substitute`, but carrying rows of E instead of bits
+        // of a single board. Free buttons are never pressed in the particular solution, so their rows stay empty.
+        let mut inverse = vec![BitVec::new(size); size];
+        for (row, &pivot) in pivots.iter().enumerate().rev() {
+            let mut operations = matrix[row].slice(offset, size);
+            for col in matrix[row]
+                .ones()
+                .take_while(|&col| col < size)
+                .filter(|&col| col != pivot)
+            {
+                operations.xor(&inverse[col]);
+            }
+            inverse[pivot] = operations;
+        }
+
+        SolutionOperator {
+            inverse,
+            checks: find_checks(graph),
+            kernel_basis: find_kernel_basis(&matrix, &pivots, size),
+        }
+    }
+
+    // The presses with the fewest buttons that turn the lights off, or the checks the lights fail.
+    pub(super) fn solve(&self, lights: &BitVec) -> Result<BitVec, Vec<BitVec>> {
+        let violated_checks: Vec<BitVec> = self
+            .checks
+            .iter()
+            .filter(|check| check.odd_overlap(lights))
+            .cloned()
+            .collect();
+        if !violated_checks.is_empty() {
+            return Err(violated_checks);
+        }
+
+        let solution_vector =
+            BitVec::from_bools(self.inverse.iter().map(|row| row.odd_overlap(lights)));
+
+        Ok(find_minimum_solution(solution_vector, &self.kernel_basis))
+    }
+
+    // Writes the operator as little-endian 64-bit words: the file version, the graph (its node count, then each
+    // node's toggle count and toggles) so a file is only ever read back for the same graph, and then the inverse,
+    // checks and kernel basis, each as a count of vectors followed by their words.
+    fn save(&self, graph: &Graph, path: &Path) -> io::Result<()> {
+        let size = graph.node_count();
+        let mut words = vec![FILE_VERSION, size as u64];
+        for node in 0..size {
+            let toggles = graph.toggled_by(node);
+            words.push(toggles.len() as u64);
+            words.extend(toggles.iter().map(|&other| other as u64));
+        }
+        for vectors in [&self.inverse, &self.checks, &self.kernel_basis] {
+            words.push(vectors.len() as u64);
+            for vector in vectors {
+                words.extend_from_slice(vector.words());
+            }
+        }
+
+        if let Some(directory) = path.parent() {
+            fs::create_dir_all(directory)?;
+        }
+        let bytes: Vec<u8> = words.iter().flat_map(|word| word.to_le_bytes()).collect();
+        fs::write(path, bytes)
+    }
+
+    // Reads an operator written by `save`, or None if the file is missing, damaged or for another graph.
+    fn load(graph: &Graph, path: &Path) -> Option<Self> {
+        let bytes = fs::read(path).ok()?;
+        if bytes.len() % 8 != 0 {
+            return None;
+        }
+        let mut words = bytes
+            .chunks_exact(8)
+            .map(|chunk| u64::from_le_bytes(chunk.try_into().expect("chunks are 8 bytes")));
+        let mut next = || words.next();
+
+        let size = graph.node_count();
+        if next()? != FILE_VERSION || next()? != size as u64 {
+            return None;
+        }
+        for node in 0..size {
+            let toggles = graph.toggled_by(node);
+            if next()? != toggles.len() as u64 {
+                return None;
+            }
+            for &other in toggles {
+                if next()? != other as u64 {
+                    return None;
+                }
+            }
+        }
+
+        let mut read_vectors = || -> Option<Vec<BitVec>> {
+            let count = next()?;
+            (0..count)
+                .map(|_| {
+                    let words = (0..size.div_ceil(WORD_BITS))
+                        .map(|_| next())
+                        .collect::<Option<Vec<u64>>>()?;
+                    BitVec::from_words(words, size)
+                })
+                .collect()
+        };
+        let inverse = read_vectors()?;
+        let checks = read_vectors()?;
+        let kernel_basis = read_vectors()?;
+        if inverse.len() != size {
+            return None;
+        }
+
+        Some(SolutionOperator {
+            inverse,
+            checks,
+            kernel_basis,
+        })
+    }
+}
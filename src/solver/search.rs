@@ -0,0 +1,205 @@
+// Breadth-first search over every arrangement of a small board's lights. The lit-only solver searches forward from
+// the board, as the order of presses matters there; `solve_by_search` searches back from the target once, which
+// answers every board of the same size, shape, rules and target, and so makes it cheap to check the other solvers
+// against it on every small board.
+//
+// Each board is numbered by reading its lights as the digits of a base-k number, first cell lowest. With classic
+// two-state lights that is a bitboard, and a press is a single XOR with the mask of the cells it toggles.
+use super::cache::Cache;
+use super::SolveError;
+use crate::{Game, Graph, NumberBoard, Point, Square};
+use std::collections::VecDeque;
+
+// The search keeps a byte for every possible board (32 MiB at this limit), so it only runs on small boards:
+// up to 25 classic lights, or 15 lights with three states.
+const MAX_SEARCH_BOARDS: u64 = 1 << 25;
+
+// Marks the board the search started from, which no button press led to.
+pub(super) const START: u8 = u8::MAX;
+
+// Each table holds a byte per board, up to 32 MiB, and a new one is made for every target, so only a couple are kept
+const MAX_TABLES: usize = 2;
+
+// Tables are keyed by the graph, the number of states and the target's number
+static TABLES: Cache<(Graph, u64, u32), SearchTable> = Cache::with_capacity(MAX_TABLES);
+
+/// Finds the fewest presses that bring the board to its target by trying them all: a breadth-first search over
+/// every arrangement of the board's lights. It shares nothing with `solve_lights_out` beyond the board's graph,
+/// so it is a check on that solver, but it only runs on boards with up to 2^25 arrangements of their lights,
+/// such as 5x5 classic boards.
+///
+/// The search is made once for each size, shape, rule set and target, and remembered, so checking many boards
+/// like one another only pays for it once. The result holds the number of times to press each button.
+pub fn solve_by_search(game: &Game) -> Result<NumberBoard, SolveError> {
+    let cells = game.cells();
+    let states = game.variant().states as u64;
+    let boards = board_count(cells.len(), states)?;
+    let search = Search::new(game.graph(), states);
+    let start = search.number(game, &cells, Game::square);
+    let goal = search.number(game, &cells, Game::target_square);
+
+    let table = TABLES.get_or_insert_with(&(search.graph.clone(), states, goal), || {
+        SearchTable::new(&search, goal, boards)
+    });
+    let counts = table
+        .presses_from(&search, start)
+        .ok_or(SolveError::Unreachable)?;
+
+    let mut solution = vec![vec![0; game.width() as usize]; game.height() as usize];
+    for (point, count) in cells.iter().zip(counts) {
+        solution[point.y as usize][point.x as usize] = count;
+    }
+
+    Ok(solution)
+}
+
+// The number of arrangements of `lights` lights with `states` states, if few enough to search.
+pub(super) fn board_count(lights: usize, states: u64) -> Result<u64, SolveError> {
+    states
+        .checked_pow(lights as u32)
+        .filter(|&boards| boards <= MAX_SEARCH_BOARDS)
+        .ok_or(SolveError::SearchTooLarge { lights })
+}
+
+// The search back from one target: for every board, a press that brings it a step closer.
+struct SearchTable {
+    // The button to press next from each board, plus one; 0 for boards that never reach the target
+    next_press: Vec<u8>,
+    goal: u32,
+}
+
+impl SearchTable {
+    fn new(search: &Search, goal: u32, boards: u64) -> Self {
+        let mut next_press = vec![0u8; boards as usize];
+        next_press[goal as usize] = START;
+        let mut queue = VecDeque::from([goal]);
+
+        // Stepping back from a board, `unpress` finds each board that one press brings to it
+        while let Some(board) = queue.pop_front() {
+            for button in 0..search.graph.node_count() {
+                let previous = search.unpress(board, button);
+                if next_press[previous as usize] == 0 {
+                    next_press[previous as usize] = button as u8 + 1;
+                    queue.push_back(previous);
+                }
+            }
+        }
+
+        SearchTable { next_press, goal }
+    }
+
+    // The number of times to press each button to bring `board` to the target, if it can be.
+    fn presses_from(&self, search: &Search, mut board: u32) -> Option<Vec<i32>> {
+        let mut counts = vec![0; search.graph.node_count()];
+        while board != self.goal {
+            let button = self.next_press[board as usize].checked_sub(1)? as usize;
+            counts[button] += 1;
+            board = search.press(board, button);
+        }
+
+        Some(counts)
+    }
+}
+
+pub(super) struct Search {
+    graph: Graph,
+    states: u64,
+    // The value of one step of each cell's light in a board's number
+    place: Vec<u64>,
+    // The cells each button toggles as a bitboard, used when lights have two states
+    masks: Vec<u32>,
+}
+
+impl Search {
+    pub(super) fn new(graph: Graph, states: u64) -> Self {
+        let place: Vec<u64> = (0..graph.node_count())
+            .map(|cell| states.pow(cell as u32))
+            .collect();
+        let masks = (0..graph.node_count())
+            .map(|button| {
+                graph
+                    .toggled_by(button)
+                    .iter()
+                    .fold(0, |mask, &cell| mask | 1 << cell)
+            })
+            .collect();
+
+        Search {
+            graph,
+            states,
+            place,
+            masks,
+        }
+    }
+
+    // The number of the board with each of `cells` lit as `square` reads it from the game.
+    pub(super) fn number(
+        &self,
+        game: &Game,
+        cells: &[Point],
+        square: fn(&Game, &Point) -> Square,
+    ) -> u32 {
+        cells
+            .iter()
+            .enumerate()
+            .map(|(cell, point)| square(game, point).state() as u64 * self.place[cell])
+            .sum::<u64>() as u32
+    }
+
+    pub(super) fn digit(&self, board: u32, cell: usize) -> u64 {
+        if self.states == 2 {
+            (board >> cell & 1) as u64
+        } else {
+            board as u64 / self.place[cell] % self.states
+        }
+    }
+
+    // Advances every light the button toggles.
+    pub(super) fn press(&self, board: u32, button: usize) -> u32 {
+        if self.states == 2 {
+            return board ^ self.masks[button];
+        }
+
+        self.graph
+            .toggled_by(button)
+            .iter()
+            .fold(board as u64, |board, &cell| {
+                if self.digit(board as u32, cell) == self.states - 1 {
+                    board - (self.states - 1) * self.place[cell]
+                } else {
+                    board + self.place[cell]
+                }
+            }) as u32
+    }
+
+    // Moves every light the button toggles back a state, undoing `press`.
+    fn unpress(&self, board: u32, button: usize) -> u32 {
+        if self.states == 2 {
+            return board ^ self.masks[button];
+        }
+
+        self.graph
+            .toggled_by(button)
+            .iter()
+            .fold(board as u64, |board, &cell| {
+                if self.digit(board as u32, cell) == 0 {
+                    board + (self.states - 1) * self.place[cell]
+                } else {
+                    board - self.place[cell]
+                }
+            }) as u32
+    }
+
+    // Follows the recorded presses back from `board` to the start, returning them in the order they were made.
+    pub(super) fn presses_to(&self, mut board: u32, reached_by: &[u8]) -> Vec<usize> {
+        let mut presses = Vec::new();
+        while reached_by[board as usize] != START {
+            let button = (reached_by[board as usize] - 1) as usize;
+            board = self.unpress(board, button);
+            presses.push(button);
+        }
+        presses.reverse();
+
+        presses
+    }
+}
@@ -0,0 +1,66 @@
+use crate::{Board, Square};
+
+/// Built-in pictures a board can be solved towards, instead of turning every light off.
+/// Any other picture can be drawn in a puzzle file or on the command line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Target {
+    /// Every light off, the classic goal.
+    #[default]
+    Off,
+    /// Every light on.
+    On,
+    /// Alternating lights, with the top-left light on.
+    Checkerboard,
+    /// A heart filling the board.
+    Heart,
+}
+
+impl Target {
+    pub const ALL: [Target; 4] = [Target::Off, Target::On, Target::Checkerboard, Target::Heart];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Target::Off => "off",
+            Target::On => "on",
+            Target::Checkerboard => "checkerboard",
+            Target::Heart => "heart",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Target::ALL
+            .into_iter()
+            .find(|target| target.name().eq_ignore_ascii_case(name.trim()))
+    }
+
+    /// The picture on a `width` x `height` board, with lit cells in state 1.
+    pub fn board(&self, width: i32, height: i32) -> Board {
+        (0..height)
+            .map(|y| {
+                (0..width)
+                    .map(|x| {
+                        let lit = match self {
+                            Target::Off => false,
+                            Target::On => true,
+                            Target::Checkerboard => (x + y) % 2 == 0,
+                            Target::Heart => in_heart(x, y, width, height),
+                        };
+                        if lit {
+                            Square::ON
+                        } else {
+                            Square::OFF
+                        }
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+// Samples the heart curve (x² + y² - 1)³ ≤ x²y³ at the middle of the cell, stretched over the board.
+fn in_heart(x: i32, y: i32, width: i32, height: i32) -> bool {
+    let x = ((x as f64 + 0.5) / width as f64 * 2.0 - 1.0) * 1.25;
+    let y = (1.0 - (y as f64 + 0.5) / height as f64 * 2.0) * 1.1 + 0.15;
+
+    (x * x + y * y - 1.0).powi(3) <= x * x * y.powi(3)
+}
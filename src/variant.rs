@@ -0,0 +1,365 @@
+/// How the edges of the board behave.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Topology {
+    /// Presses at the edge only toggle the neighbours that are on the board.
+    #[default]
+    Bounded,
+    /// The board wraps around, so a press on an edge also toggles the cell on the opposite edge.
+    Torus,
+}
+
+/// The shape of the cells the board is made of.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Grid {
+    /// Square cells in rows and columns.
+    #[default]
+    Square,
+    /// Hexagonal cells, with every odd row shifted half a cell to the right so each cell has six neighbours.
+    Hex,
+}
+
+impl Grid {
+    /// The pressed cell and its six neighbours on a hex grid, as offsets from a cell in an unshifted (even) row.
+    pub const HEX_NEIGHBOURHOOD: [(i32, i32); 7] =
+        [(0, 0), (-1, 0), (1, 0), (-1, -1), (0, -1), (-1, 1), (0, 1)];
+
+    /// Converts a step of (dx, dy) from a cell in row `y` to the column change on this grid.
+    /// Hex offsets are written for unshifted rows, so moving an odd number of rows from a shifted row goes one further right.
+    pub fn column_offset(&self, y: i32, dx: i32, dy: i32) -> i32 {
+        match self {
+            Grid::Hex if y.rem_euclid(2) == 1 && dy.rem_euclid(2) == 1 => dx + 1,
+            _ => dx,
+        }
+    }
+}
+
+/// Whether a press toggles the pressed cell as well as its neighbours.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Neighbourhood {
+    /// The pressed cell and its neighbours, as in classic Lights Out (the sigma+ game).
+    #[default]
+    Closed,
+    /// Only the neighbours, leaving the pressed cell as it is (the sigma game).
+    Open,
+}
+
+/// Which lights may be pressed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Restriction {
+    /// Any light may be pressed.
+    #[default]
+    None,
+    /// Only lights that are currently on may be pressed, so the order of presses matters.
+    LitOnly,
+}
+
+/// The cells a press toggles, relative to the pressed cell.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub enum Pattern {
+    /// The pressed cell and its four orthogonal neighbours.
+    #[default]
+    Plus,
+    /// The pressed cell and its four diagonal neighbours.
+    X,
+    /// The 3x3 square around the pressed cell.
+    Square,
+    /// The pressed cell and the eight cells a knight's move away.
+    Knight,
+    /// The pressed cell and the cells up to two steps away in a straight line.
+    BigPlus,
+    /// Any set of offsets, read from a stencil such as `.#./###/.#.` centred on the pressed cell.
+    Custom(Vec<(i32, i32)>),
+}
+
+impl Pattern {
+    /// The (dx, dy) offsets of the cells a press toggles.
+    pub fn offsets(&self) -> Vec<(i32, i32)> {
+        match self {
+            Pattern::Plus => vec![(0, 0), (-1, 0), (1, 0), (0, -1), (0, 1)],
+            Pattern::X => vec![(0, 0), (-1, -1), (1, -1), (-1, 1), (1, 1)],
+            Pattern::Square => (-1..=1)
+                .flat_map(|dy| (-1..=1).map(move |dx| (dx, dy)))
+                .collect(),
+            Pattern::Knight => vec![
+                (0, 0),
+                (-2, -1),
+                (-1, -2),
+                (1, -2),
+                (2, -1),
+                (-2, 1),
+                (-1, 2),
+                (1, 2),
+                (2, 1),
+            ],
+            Pattern::BigPlus => vec![
+                (0, 0),
+                (-1, 0),
+                (-2, 0),
+                (1, 0),
+                (2, 0),
+                (0, -1),
+                (0, -2),
+                (0, 1),
+                (0, 2),
+            ],
+            Pattern::Custom(offsets) => offsets.clone(),
+        }
+    }
+
+    /// Parses a stencil of rows separated by '/', with '#' for the cells a press toggles and '.' for the rest.
+    /// The stencil must have an odd width and height, as the pressed cell is its centre.
+    pub fn from_stencil(stencil: &str) -> Result<Self, String> {
+        let rows: Vec<&str> = stencil.split('/').collect();
+        let width = rows[0].chars().count();
+        if rows.iter().any(|row| row.chars().count() != width) {
+            return Err(format!(
+                "stencil '{}' has rows of different lengths",
+                stencil
+            ));
+        }
+        if width.is_multiple_of(2) || rows.len().is_multiple_of(2) {
+            return Err(format!(
+                "stencil '{}' must have an odd width and height, so it has a centre",
+                stencil
+            ));
+        }
+
+        let (center_x, center_y) = ((width / 2) as i32, (rows.len() / 2) as i32);
+        let mut offsets = Vec::new();
+        for (y, row) in rows.iter().enumerate() {
+            for (x, character) in row.chars().enumerate() {
+                match character {
+                    '#' => offsets.push((x as i32 - center_x, y as i32 - center_y)),
+                    '.' => {}
+                    _ => {
+                        return Err(format!(
+                            "invalid stencil cell '{}', expected '#' or '.'",
+                            character
+                        ))
+                    }
+                }
+            }
+        }
+        if offsets.is_empty() {
+            return Err(format!("stencil '{}' toggles no cells", stencil));
+        }
+
+        Ok(Pattern::Custom(offsets))
+    }
+
+    /// Writes the offsets as the smallest stencil centred on the pressed cell, the inverse of `from_stencil`.
+    pub fn to_stencil(&self) -> String {
+        let offsets = self.offsets();
+        let radius_x = offsets.iter().map(|(dx, _)| dx.abs()).max().unwrap_or(0);
+        let radius_y = offsets.iter().map(|(_, dy)| dy.abs()).max().unwrap_or(0);
+
+        (-radius_y..=radius_y)
+            .map(|dy| {
+                (-radius_x..=radius_x)
+                    .map(|dx| {
+                        if offsets.contains(&(dx, dy)) {
+                            '#'
+                        } else {
+                            '.'
+                        }
+                    })
+                    .collect::<String>()
+            })
+            .collect::<Vec<String>>()
+            .join("/")
+    }
+}
+
+/// The number of states used by Lights Out 2000, where each light cycles through off and two colours.
+pub const LIGHTS_OUT_2000_STATES: u8 = 3;
+
+/// The most states a light can have; puzzle files write states as a single digit or letter.
+pub const MAX_STATES: u8 = 36;
+
+/// Parses the number of states a light cycles through, as given to `states:` or `--states`.
+pub fn parse_states(value: &str) -> Result<u8, String> {
+    value
+        .trim()
+        .parse()
+        .ok()
+        .filter(|states| (2..=MAX_STATES).contains(states))
+        .ok_or_else(|| {
+            format!(
+                "invalid states '{}', expected a number from 2 to {}",
+                value, MAX_STATES
+            )
+        })
+}
+
+/// The rules a game is played by. The default is classic Lights Out.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Variant {
+    pub grid: Grid,
+    pub topology: Topology,
+    pub pattern: Pattern,
+    pub neighbourhood: Neighbourhood,
+    pub restriction: Restriction,
+    /// How many states each light cycles through, from 2 (classic on/off) up to `MAX_STATES`.
+    pub states: u8,
+}
+
+impl Default for Variant {
+    fn default() -> Self {
+        Variant {
+            grid: Grid::default(),
+            topology: Topology::default(),
+            pattern: Pattern::default(),
+            neighbourhood: Neighbourhood::default(),
+            restriction: Restriction::default(),
+            states: 2,
+        }
+    }
+}
+
+impl Variant {
+    pub fn torus() -> Self {
+        Variant {
+            topology: Topology::Torus,
+            ..Variant::default()
+        }
+    }
+
+    pub fn hex() -> Self {
+        Variant {
+            grid: Grid::Hex,
+            ..Variant::default()
+        }
+    }
+
+    /// Only the neighbours of a pressed cell toggle, not the cell itself.
+    pub fn sigma() -> Self {
+        Variant {
+            neighbourhood: Neighbourhood::Open,
+            ..Variant::default()
+        }
+    }
+
+    /// Only lights that are on may be pressed.
+    pub fn lit_only() -> Self {
+        Variant {
+            restriction: Restriction::LitOnly,
+            ..Variant::default()
+        }
+    }
+
+    /// The (dx, dy) offsets of the cells a press toggles: the pattern on a square grid, or the six neighbours on a hex grid,
+    /// without the pressed cell in the sigma game.
+    pub fn neighbourhood(&self) -> Vec<(i32, i32)> {
+        let offsets = match self.grid {
+            Grid::Square => self.pattern.offsets(),
+            Grid::Hex => Grid::HEX_NEIGHBOURHOOD.to_vec(),
+        };
+        match self.neighbourhood {
+            Neighbourhood::Closed => offsets,
+            Neighbourhood::Open => offsets
+                .into_iter()
+                .filter(|&offset| offset != (0, 0))
+                .collect(),
+        }
+    }
+
+    pub fn with_pattern(pattern: Pattern) -> Self {
+        Variant {
+            pattern,
+            ..Variant::default()
+        }
+    }
+
+    /// Lights that cycle through `states` states, e.g. 3 for Lights Out 2000, or `None` unless `states` is
+    /// from 2 up to `MAX_STATES`.
+    pub fn multi_state(states: u8) -> Option<Self> {
+        (2..=MAX_STATES).contains(&states).then(|| Variant {
+            states,
+            ..Variant::default()
+        })
+    }
+
+    /// Checks that the rules can be played on a board with `height` rows. Hex rows alternate between unshifted and
+    /// shifted, so a hex torus needs an even number of rows for that to carry on across the wrap; with an odd
+    /// number, some presses near the wrap would toggle cells that do not toggle them back.
+    pub fn check_height(&self, height: i32) -> Result<(), String> {
+        if self.grid == Grid::Hex && self.topology == Topology::Torus && height % 2 != 0 {
+            return Err(format!(
+                "a hex torus needs an even number of rows, but the board has {}",
+                height
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// The variant's rules written as keywords, e.g. "classic" or "torus knight", as used by puzzle files and the command line.
+    /// The number of states is kept separately, as the `states` header and `--states` option.
+    pub fn name(&self) -> String {
+        let mut keywords = Vec::new();
+        if self.grid == Grid::Hex {
+            keywords.push("hex".to_string());
+        }
+        if self.topology == Topology::Torus {
+            keywords.push("torus".to_string());
+        }
+        match &self.pattern {
+            Pattern::Plus => {}
+            Pattern::X => keywords.push("x".to_string()),
+            Pattern::Square => keywords.push("square".to_string()),
+            Pattern::Knight => keywords.push("knight".to_string()),
+            Pattern::BigPlus => keywords.push("big-plus".to_string()),
+            pattern => keywords.push(format!("stencil={}", pattern.to_stencil())),
+        }
+        if self.neighbourhood == Neighbourhood::Open {
+            keywords.push("sigma".to_string());
+        }
+        if self.restriction == Restriction::LitOnly {
+            keywords.push("lit-only".to_string());
+        }
+
+        if keywords.is_empty() {
+            "classic".to_string()
+        } else {
+            keywords.join(" ")
+        }
+    }
+
+    /// Parses keywords separated by spaces or commas, the inverse of `name`.
+    pub fn from_name(name: &str) -> Result<Self, String> {
+        let mut variant = Variant::default();
+        for keyword in name
+            .split(|character: char| character.is_whitespace() || character == ',')
+            .filter(|keyword| !keyword.is_empty())
+        {
+            match keyword.to_lowercase().as_str() {
+                "classic" => {}
+                "hex" => variant.grid = Grid::Hex,
+                "torus" => variant.topology = Topology::Torus,
+                "sigma" => variant.neighbourhood = Neighbourhood::Open,
+                "sigma-plus" => variant.neighbourhood = Neighbourhood::Closed,
+                "lit-only" => variant.restriction = Restriction::LitOnly,
+                "plus" => variant.pattern = Pattern::Plus,
+                "x" => variant.pattern = Pattern::X,
+                "square" => variant.pattern = Pattern::Square,
+                "knight" => variant.pattern = Pattern::Knight,
+                "big-plus" => variant.pattern = Pattern::BigPlus,
+                keyword => match keyword.strip_prefix("stencil=") {
+                    Some(stencil) => variant.pattern = Pattern::from_stencil(stencil)?,
+                    None => {
+                        return Err(format!(
+                            "unknown variant '{}', expected 'classic', 'hex', 'torus', 'sigma', \
+                             'sigma-plus', 'lit-only', a pattern (plus, x, square, knight, big-plus) or 'stencil=ROWS'",
+                            keyword
+                        ))
+                    }
+                },
+            }
+        }
+        if variant.grid == Grid::Hex && variant.pattern != Pattern::Plus {
+            return Err("press patterns only apply to square grids, not 'hex'".to_string());
+        }
+
+        Ok(variant)
+    }
+}
@@ -0,0 +1,108 @@
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use terminal_lights_out::{set_cache_directory, solve_lights_out, Game, Point};
+
+// A long time ago, so a file that is written again can be told apart from one that was only read.
+fn long_ago() -> SystemTime {
+    SystemTime::UNIX_EPOCH + Duration::from_secs(86_400)
+}
+
+fn modified(path: &Path) -> SystemTime {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .expect("the table file exists")
+}
+
+fn set_modified(path: &Path, time: SystemTime) {
+    File::options()
+        .write(true)
+        .open(path)
+        .and_then(|file| file.set_modified(time))
+        .expect("the table file exists");
+}
+
+fn table_files(directory: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = fs::read_dir(directory)
+        .expect("the cache directory exists")
+        .map(|entry| entry.expect("the directory can be read").path())
+        .collect();
+    files.sort();
+
+    files
+}
+
+// Tables are kept in memory too, and at most 16 of them, so solving more boards than that of other sizes makes
+// the next solve of a board go to the directory.
+fn forget_tables_in_memory() {
+    for width in 1..=32 {
+        solve_lights_out(&Game::new(width, 1).expect("the size is valid"))
+            .expect("boards with every light off are solvable");
+    }
+}
+
+fn game(width: i32, height: i32) -> Game {
+    let mut game = Game::new(width, height).expect("the size is valid");
+    for (x, y) in [(0, 0), (2, 1), (1, 3), (3, 3)] {
+        game.toggle_light(&Point::new(x, y));
+    }
+
+    game
+}
+
+// The cache directory is shared by the whole process, so everything that uses it is in this one test.
+#[test]
+fn tables_are_read_back_and_rebuilt_when_they_do_not_fit() {
+    let directory = std::env::temp_dir().join(format!("lights-out-cache-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&directory);
+    set_cache_directory(Some(directory.clone()));
+
+    // The first solve writes the table, and the next one reads it back
+    let board = game(5, 5);
+    let solution = solve_lights_out(&board).expect("the board is solvable");
+    let files = table_files(&directory);
+    assert_eq!(files.len(), 1);
+    let file = files[0].clone();
+    let saved = fs::read(&file).expect("the table was written");
+
+    // Without the file, the table is worked out and written again, so it was no longer in memory
+    fs::remove_file(&file).expect("the table was written");
+    forget_tables_in_memory();
+    assert_eq!(solve_lights_out(&board), Ok(solution.clone()));
+    assert_eq!(fs::read(&file).expect("the table was written again"), saved);
+
+    forget_tables_in_memory();
+    set_modified(&file, long_ago());
+    assert_eq!(solve_lights_out(&board), Ok(solution.clone()));
+    assert_eq!(modified(&file), long_ago(), "the table was not read back");
+
+    // A file from another version of the format is rebuilt
+    let mut other_version = saved.clone();
+    other_version[..8].copy_from_slice(&999u64.to_le_bytes());
+    fs::write(&file, other_version).expect("the table can be written");
+    set_modified(&file, long_ago());
+    forget_tables_in_memory();
+    assert_eq!(solve_lights_out(&board), Ok(solution.clone()));
+    assert_ne!(modified(&file), long_ago(), "the table was not rebuilt");
+    assert_eq!(fs::read(&file).expect("the table was written"), saved);
+
+    // So is a file written for another graph, as if two graphs' file names were the same
+    let files = table_files(&directory);
+    let other_board = game(6, 4);
+    let other_solution = solve_lights_out(&other_board).expect("the board is solvable");
+    let other_file = table_files(&directory)
+        .into_iter()
+        .find(|path| !files.contains(path))
+        .expect("the other board's table was written");
+    let other_saved = fs::read(&other_file).expect("the table was written");
+    fs::write(&other_file, &saved).expect("the table can be written");
+    forget_tables_in_memory();
+    assert_eq!(solve_lights_out(&other_board), Ok(other_solution));
+    assert_eq!(
+        fs::read(&other_file).expect("the table was written"),
+        other_saved
+    );
+
+    set_cache_directory(None);
+    let _ = fs::remove_dir_all(&directory);
+}
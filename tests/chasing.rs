@@ -0,0 +1,87 @@
+mod common;
+
+use common::{solves, total_presses};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use terminal_lights_out::{
+    solve_by_chasing, solve_lights_out, Board, Game, SolveError, Square, Target, Variant,
+};
+
+fn random_board(rand: &mut impl Rng, width: usize, height: usize) -> Board {
+    (0..height)
+        .map(|_| {
+            (0..width)
+                .map(|_| Square::new(rand.gen_range(0..2)))
+                .collect()
+        })
+        .collect()
+}
+
+fn random_game(rand: &mut impl Rng, variant: &str) -> Game {
+    let width = rand.gen_range(1..=12);
+    let height = rand.gen_range(1..=12);
+    let mut game =
+        Game::from_board(random_board(rand, width, height)).expect("rows are the same length");
+    game.set_variant(Variant::from_name(variant).expect("the variant is valid"));
+    match rand.gen_range(0..3) {
+        0 => {}
+        1 => game.set_target(random_board(rand, width, height)),
+        _ => game.set_target(
+            Target::ALL[rand.gen_range(0..Target::ALL.len())].board(width as i32, height as i32),
+        ),
+    }
+
+    game
+}
+
+#[test]
+fn chasing_agrees_with_elimination_on_random_boards() {
+    let mut rand = ChaCha8Rng::seed_from_u64(23);
+    for variant in ["classic", "sigma"] {
+        for _ in 0..300 {
+            let game = random_game(&mut rand, variant);
+            match (solve_by_chasing(&game), solve_lights_out(&game)) {
+                (Ok(chased), Ok(eliminated)) => {
+                    assert_eq!(total_presses(&chased), total_presses(&eliminated));
+                    assert!(solves(game, &chased));
+                }
+                (
+                    Err(SolveError::Unsolvable {
+                        violated_checks, ..
+                    }),
+                    Err(SolveError::Unsolvable { .. }),
+                ) => assert!(!violated_checks.is_empty()),
+                (chased, eliminated) => panic!(
+                    "the solvers disagree on\n{:?}\nchasing: {:?}\nelimination: {:?}",
+                    game, chased, eliminated
+                ),
+            }
+        }
+    }
+}
+
+#[test]
+fn chasing_solves_large_boards() {
+    let mut rand = ChaCha8Rng::seed_from_u64(200);
+    let mut game = Game::new(100, 100).expect("the size is valid");
+    game.generate_board_with(&mut rand, Default::default())
+        .expect("the board has lights to press");
+
+    let solution = solve_by_chasing(&game).expect("generated boards are solvable");
+    assert!(solves(game, &solution));
+}
+
+#[test]
+fn chasing_falls_back_for_other_rules() {
+    let mut rand = ChaCha8Rng::seed_from_u64(5);
+    let mut game = Game::with_variant(
+        6,
+        6,
+        Variant::from_name("torus knight").expect("the variant is valid"),
+    )
+    .expect("the size is valid");
+    game.generate_board_with(&mut rand, Default::default())
+        .expect("the board has lights to press");
+
+    assert_eq!(solve_by_chasing(&game), solve_lights_out(&game));
+}
@@ -0,0 +1,18 @@
+use terminal_lights_out::{Game, NumberBoard, Point};
+
+pub fn total_presses(solution: &NumberBoard) -> i32 {
+    solution.iter().flatten().sum()
+}
+
+// Whether pressing each button the number of times in `solution` brings the board to its target.
+pub fn solves(mut game: Game, solution: &NumberBoard) -> bool {
+    for (y, row) in solution.iter().enumerate() {
+        for (x, &count) in row.iter().enumerate() {
+            for _ in 0..count {
+                game.toggle_light(&Point::new(x as i32, y as i32));
+            }
+        }
+    }
+
+    game.solved()
+}
@@ -0,0 +1,54 @@
+use terminal_lights_out::file::{parse_puzzle, write_puzzle, ParseError};
+use terminal_lights_out::{Game, Point, Square, Variant};
+
+fn parse_error(input: &str) -> ParseError {
+    parse_puzzle(input).expect_err("the puzzle is invalid")
+}
+
+#[test]
+fn written_puzzles_parse_back_to_the_same_game() {
+    let mut variant =
+        Variant::from_name("torus stencil=#.#/.#./#.#").expect("the variant is valid");
+    variant.states = 3;
+    let mut game = Game::with_variant(4, 3, variant).expect("the size is valid");
+    game.set_mask(vec![
+        vec![false, true, true, true],
+        vec![true, true, true, true],
+        vec![true, true, true, false],
+    ]);
+    game.set_target(vec![
+        vec![Square::OFF, Square::new(1), Square::OFF, Square::new(2)],
+        vec![Square::new(2), Square::OFF, Square::OFF, Square::OFF],
+        vec![Square::OFF, Square::OFF, Square::new(1), Square::OFF],
+    ]);
+    game.set_seed(Some(42));
+    for (x, y, presses) in [(1, 0, 1), (2, 1, 2), (0, 2, 1), (3, 1, 1)] {
+        for _ in 0..presses {
+            game.toggle_single_light(&Point::new(x, y));
+        }
+    }
+
+    let parsed = parse_puzzle(&write_puzzle(&game)).expect("written puzzles are valid");
+    assert_eq!(parsed, game);
+}
+
+#[test]
+fn bad_lights_are_reported_with_their_line_and_column() {
+    let error = parse_error("size: 3x2\n; a comment\n#.#\n.x.\n");
+    assert_eq!(error.line, 4);
+    assert!(error.message.contains("'x' in column 2"), "{}", error);
+}
+
+#[test]
+fn rows_of_different_lengths_are_reported_on_the_shorter_row() {
+    let error = parse_error("#.#\n...\n..\n");
+    assert_eq!(error.line, 3);
+    assert_eq!(error.message, "row has 2 lights, but the first row has 3");
+}
+
+#[test]
+fn headers_after_the_board_are_reported() {
+    let error = parse_error("#.#\n\nvariant: torus\n");
+    assert_eq!(error.line, 3);
+    assert_eq!(error.message, "header lines must come before the board");
+}
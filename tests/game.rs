@@ -0,0 +1,80 @@
+use terminal_lights_out::file::parse_puzzle;
+use terminal_lights_out::variant::MAX_STATES;
+use terminal_lights_out::{Game, Square, Variant};
+
+#[test]
+fn states_out_of_range_are_rejected() {
+    for states in [0, 1, MAX_STATES + 1] {
+        assert_eq!(Variant::multi_state(states), None);
+
+        let variant = Variant {
+            states,
+            ..Variant::default()
+        };
+        assert_eq!(Game::with_variant(3, 3, variant), None);
+    }
+    assert!(Game::with_variant(3, 3, Variant::multi_state(MAX_STATES).unwrap()).is_some());
+}
+
+#[test]
+#[should_panic(expected = "lights must have from 2 to 36 states")]
+fn setting_a_variant_without_states_panics() {
+    let mut game = Game::new(3, 3).expect("the size is valid");
+    game.set_variant(Variant {
+        states: 0,
+        ..Variant::default()
+    });
+}
+
+#[test]
+fn hex_tori_need_an_even_number_of_rows() {
+    let hex_torus = Variant::from_name("hex torus").expect("the variant is valid");
+    assert_eq!(Game::with_variant(3, 3, hex_torus.clone()), None);
+    assert!(parse_puzzle("variant: hex torus\n...\n...\n...\n").is_err());
+
+    for (width, height) in [(3, 4), (4, 2), (5, 6)] {
+        let game =
+            Game::with_variant(width, height, hex_torus.clone()).expect("the height is even");
+        for point in game.cells() {
+            for neighbour in game.toggled_by(&point) {
+                assert!(
+                    game.toggled_by(&neighbour).contains(&point),
+                    "{:?} toggles {:?} on a {}x{} hex torus, but not the other way",
+                    point,
+                    neighbour,
+                    width,
+                    height
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn boards_get_enough_states_for_their_lights() {
+    let game =
+        Game::from_board(vec![vec![Square::new(2), Square::OFF]]).expect("the board is valid");
+    assert_eq!(game.variant().states, 3);
+    assert_eq!(
+        Game::from_board(vec![vec![Square::ON]])
+            .expect("the board is valid")
+            .variant()
+            .states,
+        2
+    );
+    assert_eq!(Game::from_board(vec![vec![Square::new(MAX_STATES)]]), None);
+}
+
+#[test]
+#[should_panic(expected = "lights must be in states below the 2 states given")]
+fn setting_a_variant_with_too_few_states_panics() {
+    let mut game = Game::from_board(vec![vec![Square::new(7)]]).expect("the board is valid");
+    game.set_variant(Variant::default());
+}
+
+#[test]
+#[should_panic(expected = "lights must be in states below the 2 states given")]
+fn setting_a_target_past_the_states_panics() {
+    let mut game = Game::new(1, 1).expect("the size is valid");
+    game.set_target(vec![vec![Square::new(255)]]);
+}
@@ -0,0 +1,93 @@
+use terminal_lights_out::file::write_puzzle;
+use terminal_lights_out::{
+    solve_lights_out, Difficulty, Game, Generation, GenerationError, Variant,
+};
+
+#[test]
+fn generated_boards_are_never_solved() {
+    for seed in 0..50 {
+        for generation in [
+            Generation::UniformSolvable,
+            Generation::RandomPresses,
+            Generation::Difficulty(Difficulty::Easy),
+        ] {
+            let mut game = Game::new(3, 3).expect("the size is valid");
+            assert_eq!(game.generate_board(seed, generation), Ok(()));
+            assert!(!game.solved());
+        }
+    }
+}
+
+#[test]
+fn boards_that_are_always_solved_are_reported() {
+    let mut sigma = Game::with_variant(1, 1, Variant::sigma()).expect("the size is valid");
+    assert_eq!(
+        sigma.generate_board(2, Generation::UniformSolvable),
+        Err(GenerationError::AlwaysSolved)
+    );
+    assert!(sigma.solved());
+
+    for generation in [
+        Generation::UniformSolvable,
+        Generation::RandomPresses,
+        Generation::Difficulty(Difficulty::Easy),
+    ] {
+        let mut holes = Game::new(3, 3).expect("the size is valid");
+        holes.set_mask(vec![vec![false; 3]; 3]);
+        assert_eq!(
+            holes.generate_board(1, generation),
+            Err(GenerationError::AlwaysSolved)
+        );
+    }
+}
+
+#[test]
+fn difficulties_generate_boards_in_their_range() {
+    for (width, height) in [(3, 3), (4, 4), (5, 5), (9, 9), (7, 4)] {
+        let game = Game::new(width, height).expect("the size is valid");
+        let longest = game.longest_solution();
+        for difficulty in Difficulty::ALL {
+            let range = difficulty.press_range(longest);
+            for seed in 0..5 {
+                let mut game = game.clone();
+                assert_eq!(
+                    game.generate_board(seed, Generation::Difficulty(difficulty)),
+                    Ok(())
+                );
+                let presses: i32 = solve_lights_out(&game)
+                    .expect("generated boards are solvable")
+                    .iter()
+                    .flatten()
+                    .sum();
+                assert!(
+                    range.contains(&presses),
+                    "{}x{} {:?} board needs {} presses, outside {:?}",
+                    width,
+                    height,
+                    difficulty,
+                    presses,
+                    range
+                );
+            }
+        }
+    }
+}
+
+// A seed must give the same board on every machine and every release, so these boards must never change
+#[test]
+fn seeds_generate_the_same_boards() {
+    let mut game = Game::new(5, 5).expect("the size is valid");
+    game.generate_board(42, Generation::RandomPresses)
+        .expect("the board has lights to press");
+    assert_eq!(
+        write_puzzle(&game),
+        "size: 5x5\nvariant: classic\nseed: 42\n....#\n#.##.\n..#.#\n.##..\n.....\n"
+    );
+
+    game.generate_board(42, Generation::UniformSolvable)
+        .expect("the board has lights to press");
+    assert_eq!(
+        write_puzzle(&game),
+        "size: 5x5\nvariant: classic\nseed: 42\n####.\n#..#.\n.....\n..##.\n####.\n"
+    );
+}
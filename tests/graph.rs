@@ -0,0 +1,33 @@
+use terminal_lights_out::graph::parse_graph;
+use terminal_lights_out::Square;
+
+#[test]
+fn graphs_are_parsed_from_edges_and_adjacency_lists() {
+    let game = parse_graph(
+        "; a 4-cycle with a tail\nnodes: 5\nlights: 1 0 1 0 0\n0 1\n1 2\n2 3\n3 0\n3: 4\n",
+    )
+    .expect("the graph is valid");
+
+    assert_eq!(game.graph().node_count(), 5);
+    assert_eq!(game.graph().neighbours(3), vec![2, 0, 4]);
+    assert_eq!(game.graph().toggled_by(4), &[4, 3]);
+    assert_eq!(game.square(0), Square::new(1));
+    assert_eq!(game.square(1), Square::OFF);
+}
+
+#[test]
+fn edges_past_the_last_node_are_reported_on_their_line() {
+    let error = parse_graph("nodes: 3\n0 1\n\n1 3\n").expect_err("node 3 does not exist");
+    assert_eq!(error.line, 4);
+    assert_eq!(error.message, "edge 1 3 names a node past the last node, 2");
+}
+
+#[test]
+fn bad_states_are_reported_on_their_line() {
+    let error = parse_graph("0 1\nstates: 99\n").expect_err("lights have at most 36 states");
+    assert_eq!(error.line, 2);
+    assert_eq!(
+        error.message,
+        "invalid states '99', expected a number from 2 to 36"
+    );
+}
@@ -0,0 +1,106 @@
+use terminal_lights_out::{
+    solve_lights_out, solve_lit_only, Game, Point, SolveError, Square, Variant,
+};
+
+// The 3x3 board whose lights are the bits of `number`, first cell lowest.
+fn numbered_game(number: usize, variant: &Variant) -> Game {
+    let board = (0..3)
+        .map(|y| {
+            (0..3)
+                .map(|x| Square::new((number >> (y * 3 + x) & 1) as u8))
+                .collect()
+        })
+        .collect();
+    let mut game = Game::from_board(board).expect("rows are the same length");
+    game.set_variant(variant.clone());
+
+    game
+}
+
+fn number(game: &Game) -> usize {
+    game.board()
+        .iter()
+        .flatten()
+        .enumerate()
+        .map(|(cell, square)| (square.state() as usize) << cell)
+        .sum()
+}
+
+// The fewest lit-only presses that turn off every light of each board, if it can be done, found by growing the
+// set of boards that can be solved in one more press until it stops changing.
+fn lit_only_distances(variant: &Variant) -> Vec<Option<usize>> {
+    let mut distances = vec![None; 512];
+    distances[0] = Some(0);
+    for distance in 1.. {
+        let solved_before = distances.clone();
+        for (board, board_distance) in distances.iter_mut().enumerate() {
+            if board_distance.is_some() {
+                continue;
+            }
+            let game = numbered_game(board, variant);
+            let one_press_from_solved = game.cells().into_iter().any(|point| {
+                let mut next = game.clone();
+                next.toggle_light(&point);
+                game.can_press(&point) && solved_before[number(&next)] == Some(distance - 1)
+            });
+            if one_press_from_solved {
+                *board_distance = Some(distance);
+            }
+        }
+        if distances == solved_before {
+            break;
+        }
+    }
+
+    distances
+}
+
+#[test]
+fn every_3x3_board_is_solved_pressing_only_lit_lights() {
+    for rules in ["classic", "torus", "sigma", "hex"] {
+        let variant =
+            Variant::from_name(&format!("{} lit-only", rules)).expect("the variant is valid");
+        let distances = lit_only_distances(&variant);
+
+        for (board, distance) in distances.into_iter().enumerate() {
+            let game = numbered_game(board, &variant);
+            match (solve_lit_only(&game), distance) {
+                (Ok(presses), Some(distance)) => {
+                    assert_eq!(presses.len(), distance, "{} board {:09b}", rules, board);
+                    let mut replay = game.clone();
+                    for point in &presses {
+                        assert!(
+                            replay.can_press(point),
+                            "{} board {:09b} presses the unlit light {:?}",
+                            rules,
+                            board,
+                            point
+                        );
+                        replay.toggle_light(point);
+                    }
+                    assert!(replay.solved(), "{} board {:09b}", rules, board);
+                }
+                (Err(SolveError::LitOnlyUnsolvable), None) => {
+                    assert!(solve_lights_out(&game).is_ok());
+                }
+                (Err(SolveError::Unsolvable { .. }), None) => {
+                    assert!(solve_lights_out(&game).is_err());
+                }
+                (result, distance) => panic!(
+                    "{} board {:09b}: solved as {:?}, but the fewest lit-only presses are {:?}",
+                    rules, board, result, distance
+                ),
+            }
+        }
+    }
+}
+
+#[test]
+fn lit_only_presses_are_kept_to_lit_lights() {
+    let mut game = numbered_game(0, &Variant::lit_only());
+    let centre = Point::new(1, 1);
+    assert!(!game.can_press(&centre));
+
+    game.toggle_single_light(&centre);
+    assert!(game.can_press(&centre));
+}
@@ -0,0 +1,116 @@
+mod common;
+
+use common::{solves, total_presses};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use terminal_lights_out::{
+    solve_by_search, solve_lights_out, Board, Game, Shape, SolveError, Square, Target, Variant,
+};
+
+// The board whose lights are the base-`states` digits of `number`, first cell lowest.
+fn numbered_board(mut number: u64, width: usize, height: usize, states: u8) -> Board {
+    (0..height)
+        .map(|_| {
+            (0..width)
+                .map(|_| {
+                    let state = number % states as u64;
+                    number /= states as u64;
+                    Square::new(state as u8)
+                })
+                .collect()
+        })
+        .collect()
+}
+
+// Checks that elimination finds a solution exactly when the search does, with as few presses, and that it
+// solves the board.
+fn assert_agree(game: &Game) {
+    match (solve_lights_out(game), solve_by_search(game)) {
+        (Ok(eliminated), Ok(searched)) => {
+            assert_eq!(
+                total_presses(&eliminated),
+                total_presses(&searched),
+                "{:?}",
+                game
+            );
+            assert!(solves(game.clone(), &eliminated), "{:?}", game);
+            assert!(solves(game.clone(), &searched), "{:?}", game);
+        }
+        (Err(SolveError::Unsolvable { .. }), Err(SolveError::Unreachable)) => {}
+        (eliminated, searched) => panic!(
+            "the solvers disagree on\n{:?}\nelimination: {:?}\nsearch: {:?}",
+            game, eliminated, searched
+        ),
+    }
+}
+
+// Checks every board of every size up to `max_cells` lights.
+fn assert_agree_on_every_board(variant: &str, states: u8, max_cells: usize) {
+    let mut variant = Variant::from_name(variant).expect("the variant is valid");
+    variant.states = states;
+    for width in 1..=4 {
+        for height in 1..=4 {
+            let cells = width * height;
+            if cells > max_cells {
+                continue;
+            }
+            for number in 0..(states as u64).pow(cells as u32) {
+                let mut game = Game::from_board(numbered_board(number, width, height, states))
+                    .expect("rows are the same length");
+                game.set_variant(variant.clone());
+                assert_agree(&game);
+            }
+        }
+    }
+}
+
+#[test]
+fn every_classic_board_up_to_4x4() {
+    assert_agree_on_every_board("classic", 2, 16);
+}
+
+#[test]
+fn every_sigma_board_up_to_4x4() {
+    assert_agree_on_every_board("sigma", 2, 16);
+}
+
+#[test]
+fn every_board_with_other_rules_up_to_3x4() {
+    for variant in ["torus", "hex", "x", "torus knight", "big-plus"] {
+        assert_agree_on_every_board(variant, 2, 12);
+    }
+}
+
+#[test]
+fn every_multi_state_board_up_to_3x3() {
+    assert_agree_on_every_board("classic", 3, 9);
+    assert_agree_on_every_board("torus", 4, 6);
+}
+
+#[test]
+fn random_5x4_boards_with_targets_and_shapes() {
+    let mut rand = ChaCha8Rng::seed_from_u64(25);
+    for shape in Shape::ALL {
+        for target in Target::ALL {
+            let mut game = Game::new(5, 4).expect("the size is valid");
+            game.set_target(target.board(5, 4));
+            game.set_mask(shape.mask(5, 4));
+            for _ in 0..50 {
+                for point in game.cells() {
+                    if rand.gen_bool(0.5) {
+                        game.toggle_single_light(&point);
+                    }
+                }
+                assert_agree(&game);
+            }
+        }
+    }
+}
+
+#[test]
+fn search_refuses_large_boards() {
+    assert_eq!(
+        solve_by_search(&Game::new(6, 6).expect("the size is valid")),
+        Err(SolveError::SearchTooLarge { lights: 36 })
+    );
+}